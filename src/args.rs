@@ -0,0 +1,817 @@
+//! Command-line parsing. One option table drives both the parser and `--help`.
+
+use grim::GrimError;
+
+/// A `grim` subcommand.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Command {
+    Kill,
+    List,
+    Tree,
+    Watch,
+    Daemon,
+    Freeze,
+    Thaw,
+    Renice,
+    Rules,
+    Log,
+    Tui,
+}
+
+impl Command {
+    pub const ALL: [Command; 11] = [
+        Command::Kill,
+        Command::List,
+        Command::Tree,
+        Command::Watch,
+        Command::Daemon,
+        Command::Freeze,
+        Command::Thaw,
+        Command::Renice,
+        Command::Rules,
+        Command::Log,
+        Command::Tui,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Kill => "kill",
+            Self::List => "list",
+            Self::Tree => "tree",
+            Self::Watch => "watch",
+            Self::Daemon => "daemon",
+            Self::Freeze => "freeze",
+            Self::Thaw => "thaw",
+            Self::Renice => "renice",
+            Self::Rules => "rules",
+            Self::Log => "log",
+            Self::Tui => "tui",
+        }
+    }
+
+    pub fn about(self) -> &'static str {
+        match self {
+            Self::Kill => "Signal matching processes (default when targets are given)",
+            Self::List => "Show matching processes without signalling them",
+            Self::Tree => "Show matching processes with their descendant trees",
+            Self::Watch => "Keep checking and killing matches (same as kill --watch)",
+            Self::Daemon => "Run watch mode as a service: no prompts, SIGHUP reloads",
+            Self::Freeze => "Pause matching processes with SIGSTOP",
+            Self::Thaw => "Resume processes with SIGCONT (default: all frozen by grim)",
+            Self::Renice => {
+                "Change the nice value of matching processes (renice <target> <niceness>)"
+            }
+            Self::Rules => "Show what each rule in a rules file would hit (rules check <file>)",
+            Self::Log => "Show the audit log of processes grim has signalled",
+            Self::Tui => "Launch the full-screen interactive TUI (default with no arguments)",
+        }
+    }
+
+    fn parse(input: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|command| command.name() == input)
+    }
+}
+
+/// Commands that select processes from targets and filters.
+const SELECTING: &[Command] = &[
+    Command::Kill,
+    Command::List,
+    Command::Tree,
+    Command::Watch,
+    Command::Daemon,
+    Command::Freeze,
+    Command::Thaw,
+    Command::Renice,
+];
+/// Commands that act on the matched processes.
+const SIGNALLING: &[Command] = &[
+    Command::Kill,
+    Command::Watch,
+    Command::Daemon,
+    Command::Freeze,
+    Command::Thaw,
+    Command::Renice,
+];
+/// Commands that send a signal of the user's choosing, possibly repeatedly.
+const KILLING: &[Command] = &[Command::Kill, Command::Watch, Command::Daemon];
+
+/// One command-line option.
+pub struct OptSpec {
+    pub long: &'static str,
+    pub short: Option<char>,
+    /// Placeholder for the option's value, or `None` for a plain flag.
+    pub value: Option<&'static str>,
+    pub help: &'static str,
+    /// Subcommands that accept this option.
+    pub commands: &'static [Command],
+    /// `--help` puts a blank line between groups.
+    pub group: u8,
+}
+
+impl OptSpec {
+    pub fn applies_to(&self, command: Command) -> bool {
+        self.commands.contains(&command)
+    }
+}
+
+const fn opt(
+    group: u8,
+    long: &'static str,
+    short: Option<char>,
+    value: Option<&'static str>,
+    commands: &'static [Command],
+    help: &'static str,
+) -> OptSpec {
+    OptSpec {
+        long,
+        short,
+        value,
+        help,
+        commands,
+        group,
+    }
+}
+
+pub const OPTIONS: &[OptSpec] = &[
+    opt(
+        0,
+        "interactive",
+        Some('i'),
+        None,
+        &[Command::Kill],
+        "Launch full-screen interactive TUI",
+    ),
+    opt(
+        0,
+        "force",
+        Some('f'),
+        None,
+        SIGNALLING,
+        "Kill without confirmation",
+    ),
+    opt(
+        0,
+        "kill-children",
+        Some('c'),
+        None,
+        SIGNALLING,
+        "Also signal all descendant processes (leaves first)",
+    ),
+    opt(
+        0,
+        "dry-run",
+        Some('n'),
+        None,
+        SIGNALLING,
+        "Show what would be killed without sending signals",
+    ),
+    opt(
+        0,
+        "yes-many",
+        None,
+        None,
+        SIGNALLING,
+        "Allow signalling more processes than the safety limit",
+    ),
+    opt(
+        0,
+        "signal",
+        Some('s'),
+        Some("name|num"),
+        KILLING,
+        "Signal to send, e.g. TERM, HUP, 15 (default: KILL, TERM with --grace)",
+    ),
+    opt(
+        0,
+        "grace",
+        Some('g'),
+        Some("duration"),
+        &[Command::Kill, Command::Watch, Command::Daemon, Command::Tui],
+        "Send TERM first, KILL if still alive after e.g. 5s, 1m",
+    ),
+    opt(
+        0,
+        "wait",
+        None,
+        Some("duration"),
+        &[Command::Kill, Command::Watch, Command::Daemon, Command::Tui],
+        "Check that killed processes are gone within e.g. 2s, 500ms",
+    ),
+    opt(
+        0,
+        "reason",
+        None,
+        Some("text"),
+        &[
+            Command::Kill,
+            Command::Watch,
+            Command::Daemon,
+            Command::Freeze,
+            Command::Thaw,
+            Command::Renice,
+            Command::Tui,
+        ],
+        "Why you are signalling, recorded in the audit log",
+    ),
+    opt(
+        1,
+        "exact",
+        Some('e'),
+        None,
+        SELECTING,
+        "Match process name exactly",
+    ),
+    opt(
+        1,
+        "regex",
+        Some('E'),
+        None,
+        SELECTING,
+        "Treat names as regular expressions",
+    ),
+    opt(
+        1,
+        "glob",
+        None,
+        None,
+        SELECTING,
+        "Treat names as shell globs (*, ?, [...])",
+    ),
+    opt(
+        1,
+        "case-sensitive",
+        None,
+        None,
+        SELECTING,
+        "Do not ignore case when matching names",
+    ),
+    opt(
+        1,
+        "field",
+        None,
+        Some("list"),
+        SELECTING,
+        "Fields to match: name,cmd,exe (default: name,cmd)",
+    ),
+    opt(
+        1,
+        "port",
+        Some('p'),
+        Some("port[/tcp|udp]"),
+        SELECTING,
+        "Target processes with a socket on this port, like :3000 (repeatable)",
+    ),
+    opt(
+        1,
+        "user",
+        Some('u'),
+        Some("name|uid"),
+        &[
+            Command::Kill,
+            Command::List,
+            Command::Tree,
+            Command::Watch,
+            Command::Daemon,
+            Command::Freeze,
+            Command::Thaw,
+            Command::Renice,
+            Command::Log,
+        ],
+        "Only match processes owned by this user",
+    ),
+    opt(
+        1,
+        "all",
+        Some('a'),
+        None,
+        SELECTING,
+        "Target every process passing the filters (needs a filter)",
+    ),
+    opt(
+        1,
+        "cpu-above",
+        None,
+        Some("percent"),
+        SELECTING,
+        "Only match processes using more CPU than this",
+    ),
+    opt(
+        1,
+        "mem-above",
+        None,
+        Some("size"),
+        SELECTING,
+        "Only match processes using more memory, e.g. 512M, 2G",
+    ),
+    opt(
+        1,
+        "older-than",
+        None,
+        Some("duration"),
+        SELECTING,
+        "Only match processes running longer than e.g. 2h, 3d",
+    ),
+    opt(
+        1,
+        "younger-than",
+        None,
+        Some("duration"),
+        SELECTING,
+        "Only match processes started within e.g. 30m",
+    ),
+    opt(
+        1,
+        "exclude",
+        Some('x'),
+        Some("pattern"),
+        &[
+            Command::Kill,
+            Command::List,
+            Command::Tree,
+            Command::Watch,
+            Command::Daemon,
+            Command::Freeze,
+            Command::Thaw,
+            Command::Renice,
+            Command::Tui,
+        ],
+        "Never match processes matching this (repeatable)",
+    ),
+    opt(
+        2,
+        "watch",
+        Some('w'),
+        None,
+        &[Command::Kill],
+        "Continuously monitor and kill matching processes",
+    ),
+    opt(
+        2,
+        "interval",
+        None,
+        Some("seconds"),
+        KILLING,
+        "Watch mode refresh interval (default: 2)",
+    ),
+    opt(
+        2,
+        "max",
+        None,
+        Some("count"),
+        KILLING,
+        "Stop after killing N processes",
+    ),
+    opt(
+        2,
+        "timeout",
+        None,
+        Some("seconds"),
+        KILLING,
+        "Stop after a time limit",
+    ),
+    opt(
+        2,
+        "for",
+        None,
+        Some("duration"),
+        KILLING,
+        "Thresholds must hold this long across watch checks",
+    ),
+    opt(
+        2,
+        "rules",
+        None,
+        Some("file"),
+        KILLING,
+        "Apply the rules in a TOML file instead of targets",
+    ),
+    opt(
+        2,
+        "pid-file",
+        None,
+        Some("path"),
+        &[Command::Daemon],
+        "Write the daemon's PID here; refuse to start if it is running",
+    ),
+    opt(
+        2,
+        "log-file",
+        None,
+        Some("path"),
+        &[Command::Daemon],
+        "Append output here instead of stdout (reopened on SIGHUP)",
+    ),
+    opt(
+        2,
+        "since",
+        None,
+        Some("duration"),
+        &[Command::Log],
+        "Only show entries from the last e.g. 2h, 3d",
+    ),
+    opt(
+        2,
+        "by",
+        None,
+        Some("user"),
+        &[Command::Log],
+        "Only show signals sent by this user (or via sudo by them)",
+    ),
+    opt(
+        3,
+        "output",
+        Some('o'),
+        Some("format"),
+        &[
+            Command::Kill,
+            Command::List,
+            Command::Tree,
+            Command::Watch,
+            Command::Daemon,
+            Command::Freeze,
+            Command::Thaw,
+            Command::Renice,
+            Command::Rules,
+            Command::Log,
+        ],
+        "text (default), json or ndjson for scripts",
+    ),
+    opt(
+        3,
+        "config",
+        None,
+        Some("path"),
+        &Command::ALL,
+        "Read settings from this file instead of ~/.config/grim/config.toml",
+    ),
+    opt(
+        3,
+        "no-config",
+        None,
+        None,
+        &Command::ALL,
+        "Ignore the config file",
+    ),
+    opt(
+        4,
+        "help",
+        Some('h'),
+        None,
+        &Command::ALL,
+        "Print this help message",
+    ),
+];
+
+fn find_long(name: &str) -> Option<&'static OptSpec> {
+    OPTIONS.iter().find(|opt| opt.long == name)
+}
+
+fn find_short(short: char) -> Option<&'static OptSpec> {
+    OPTIONS.iter().find(|opt| opt.short == Some(short))
+}
+
+/// The result of [`parse_args`]: which options were given, and the targets.
+#[derive(Debug)]
+pub struct ParsedArgs {
+    pub command: Command,
+    /// Whether the command was named explicitly rather than implied.
+    pub explicit_command: bool,
+    pub targets: Vec<String>,
+    /// Every option in command-line order, with its value if it takes one.
+    options: Vec<(&'static str, Option<String>)>,
+}
+
+impl ParsedArgs {
+    pub fn flag(&self, long: &str) -> bool {
+        self.options.iter().any(|(name, _)| *name == long)
+    }
+
+    /// Whichever of the `longs` flags was given last, for mutually exclusive modes.
+    pub fn last_of(&self, longs: &[&str]) -> Option<&'static str> {
+        self.options
+            .iter()
+            .rev()
+            .map(|(name, _)| *name)
+            .find(|name| longs.contains(name))
+    }
+
+    /// Every value given for a repeatable option, in order.
+    pub fn values(&self, long: &str) -> Vec<&str> {
+        self.options
+            .iter()
+            .filter(|(name, _)| *name == long)
+            .filter_map(|(_, value)| value.as_deref())
+            .collect()
+    }
+
+    /// The last value given for `long`, converted with `parse`. Unparseable values
+    /// are usage errors rather than silently replaced by a default.
+    pub fn value<T>(
+        &self,
+        long: &str,
+        parse: impl Fn(&str) -> Option<T>,
+    ) -> Result<Option<T>, GrimError> {
+        let Some(raw) = self.values(long).pop() else {
+            return Ok(None);
+        };
+        let placeholder = find_long(long).and_then(|opt| opt.value).unwrap_or("value");
+        parse(raw).map(Some).ok_or_else(|| {
+            GrimError::BadArguments(format!(
+                "Invalid value for --{}: '{}' (expected <{}>)",
+                long, raw, placeholder
+            ))
+        })
+    }
+}
+
+/// Parses `args` (without the program name). Accepts `--long value`, `--long=value`,
+/// `-s value`, `-svalue`, bundled short flags like `-fc`, and `--` to end options.
+/// A subcommand must come first; with no arguments at all grim opens the TUI.
+pub fn parse_args(args: &[String]) -> Result<ParsedArgs, GrimError> {
+    let mut rest = args;
+    let (command, explicit_command) = match args.first().map(String::as_str) {
+        None => (Command::Tui, false),
+        Some("help") => {
+            // `grim help [command]` is the same as `grim [command] --help`.
+            let command = match args.get(1) {
+                Some(name) => Command::parse(name)
+                    .ok_or_else(|| GrimError::BadArguments(format!("Unknown command: {}", name)))?,
+                None => Command::Kill,
+            };
+            return Ok(ParsedArgs {
+                command,
+                explicit_command: args.len() > 1,
+                targets: vec![],
+                options: vec![("help", None)],
+            });
+        }
+        Some(first) => match Command::parse(first) {
+            Some(command) => {
+                rest = &args[1..];
+                (command, true)
+            }
+            None => (Command::Kill, false),
+        },
+    };
+
+    let mut parsed = ParsedArgs {
+        command,
+        explicit_command,
+        targets: vec![],
+        options: vec![],
+    };
+    let mut i = 0;
+    while i < rest.len() {
+        let arg = &rest[i];
+        i += 1;
+
+        if arg == "--" {
+            parsed.targets.extend(rest[i..].iter().cloned());
+            break;
+        }
+        if let Some(long) = arg.strip_prefix("--") {
+            let (name, inline) = match long.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (long, None),
+            };
+            let opt = find_long(name)
+                .ok_or_else(|| GrimError::BadArguments(format!("Unknown option: --{}", name)))?;
+            let value = match (opt.value, inline) {
+                (Some(_), Some(value)) => Some(value),
+                (Some(placeholder), None) => {
+                    let value = rest
+                        .get(i)
+                        .cloned()
+                        .ok_or_else(|| missing(opt, placeholder))?;
+                    i += 1;
+                    Some(value)
+                }
+                (None, Some(_)) => {
+                    return Err(GrimError::BadArguments(format!(
+                        "--{} does not take a value",
+                        opt.long
+                    )));
+                }
+                (None, None) => None,
+            };
+            parsed.push(opt, value)?;
+        } else if let Some(shorts) = arg.strip_prefix('-')
+            && !shorts.is_empty()
+            // `-5` is a negative niceness for `grim renice`, not a bundle of flags.
+            && shorts.parse::<u32>().is_err()
+        {
+            for (idx, short) in shorts.char_indices() {
+                let opt = find_short(short).ok_or_else(|| {
+                    GrimError::BadArguments(format!("Unknown option: -{}", short))
+                })?;
+                let Some(placeholder) = opt.value else {
+                    parsed.push(opt, None)?;
+                    continue;
+                };
+                // A value-taking short option consumes the rest of the bundle, or the next argument.
+                let attached = &shorts[idx + short.len_utf8()..];
+                let value = if attached.is_empty() {
+                    let value = rest
+                        .get(i)
+                        .cloned()
+                        .ok_or_else(|| missing(opt, placeholder))?;
+                    i += 1;
+                    value
+                } else {
+                    attached.trim_start_matches('=').to_string()
+                };
+                parsed.push(opt, Some(value))?;
+                break;
+            }
+        } else {
+            parsed.targets.push(arg.clone());
+        }
+    }
+
+    let interactive = parsed.command == Command::Tui || parsed.flag("interactive");
+    if !parsed.flag("help") && interactive && !parsed.targets.is_empty() {
+        return Err(GrimError::BadArguments(
+            "the TUI does not take targets; use the / filter instead".to_string(),
+        ));
+    }
+    Ok(parsed)
+}
+
+impl ParsedArgs {
+    fn push(&mut self, opt: &'static OptSpec, value: Option<String>) -> Result<(), GrimError> {
+        if !opt.applies_to(self.command) {
+            return Err(GrimError::BadArguments(format!(
+                "--{} cannot be used with `grim {}`",
+                opt.long,
+                self.command.name()
+            )));
+        }
+        self.options.push((opt.long, value));
+        Ok(())
+    }
+}
+
+fn missing(opt: &OptSpec, placeholder: &str) -> GrimError {
+    GrimError::BadArguments(format!("--{} needs a value <{}>", opt.long, placeholder))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<ParsedArgs, GrimError> {
+        parse_args(&args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>())
+    }
+
+    fn rejects(args: &[&str]) -> String {
+        match parse(args) {
+            Err(GrimError::BadArguments(msg)) => msg,
+            other => panic!("expected a usage error for {:?}, got {:?}", args, other),
+        }
+    }
+
+    #[test]
+    fn no_arguments_opens_the_tui() {
+        let parsed = parse(&[]).unwrap();
+        assert_eq!(parsed.command, Command::Tui);
+        assert!(!parsed.explicit_command);
+    }
+
+    #[test]
+    fn targets_without_a_command_kill() {
+        let parsed = parse(&["chrome", "12345"]).unwrap();
+        assert_eq!(parsed.command, Command::Kill);
+        assert!(!parsed.explicit_command);
+        assert_eq!(parsed.targets, ["chrome", "12345"]);
+    }
+
+    #[test]
+    fn long_option_with_equals() {
+        let parsed = parse(&["--signal=TERM", "--grace", "5s", "node"]).unwrap();
+        assert_eq!(parsed.values("signal"), ["TERM"]);
+        assert_eq!(parsed.values("grace"), ["5s"]);
+        assert_eq!(parsed.targets, ["node"]);
+    }
+
+    #[test]
+    fn short_option_with_attached_value() {
+        let parsed = parse(&["-sTERM", "-u", "root", "node"]).unwrap();
+        assert_eq!(parsed.values("signal"), ["TERM"]);
+        assert_eq!(parsed.values("user"), ["root"]);
+        assert_eq!(parsed.targets, ["node"]);
+    }
+
+    #[test]
+    fn bundled_short_flags() {
+        let parsed = parse(&["-fc", "node"]).unwrap();
+        assert!(parsed.flag("force"));
+        assert!(parsed.flag("kill-children"));
+        assert!(!parsed.flag("dry-run"));
+
+        // A value-taking option ends the bundle.
+        let parsed = parse(&["-fsHUP", "node"]).unwrap();
+        assert!(parsed.flag("force"));
+        assert_eq!(parsed.values("signal"), ["HUP"]);
+    }
+
+    #[test]
+    fn double_dash_ends_options() {
+        let parsed = parse(&["-f", "--", "-weird", "--force", "watch"]).unwrap();
+        assert_eq!(parsed.command, Command::Kill);
+        assert_eq!(parsed.targets, ["-weird", "--force", "watch"]);
+        assert_eq!(parsed.options.len(), 1);
+    }
+
+    #[test]
+    fn negative_numbers_are_targets() {
+        let parsed = parse(&["renice", "-f", "node", "-5"]).unwrap();
+        assert!(parsed.flag("force"));
+        assert_eq!(parsed.targets, ["node", "-5"]);
+    }
+
+    #[test]
+    fn repeated_options_keep_every_value() {
+        let parsed = parse(&["-x", "sshd", "--exclude=init", "--all", "-u", "ci"]).unwrap();
+        assert_eq!(parsed.values("exclude"), ["sshd", "init"]);
+        assert_eq!(parsed.last_of(&["exact", "regex"]), None);
+        let parsed = parse(&["--regex", "--exact", "x"]).unwrap();
+        assert_eq!(parsed.last_of(&["exact", "regex", "glob"]), Some("exact"));
+    }
+
+    #[test]
+    fn unknown_options_are_rejected() {
+        assert!(rejects(&["--forse", "chrome"]).contains("--forse"));
+        assert!(rejects(&["-Z", "chrome"]).contains("-Z"));
+        assert!(rejects(&["-fZ", "chrome"]).contains("-Z"));
+    }
+
+    #[test]
+    fn flags_do_not_take_values() {
+        assert!(rejects(&["--force=yes", "chrome"]).contains("does not take a value"));
+    }
+
+    #[test]
+    fn missing_values_are_rejected() {
+        assert!(rejects(&["chrome", "--signal"]).contains("needs a value"));
+        assert!(rejects(&["chrome", "-s"]).contains("needs a value"));
+    }
+
+    #[test]
+    fn options_are_checked_against_the_command() {
+        assert!(rejects(&["list", "--signal", "TERM", "x"]).contains("grim list"));
+        assert!(rejects(&["tree", "-f", "x"]).contains("grim tree"));
+        assert!(parse(&["watch", "--interval", "5", "x"]).is_ok());
+    }
+
+    #[test]
+    fn tui_takes_no_targets() {
+        rejects(&["tui", "foo"]);
+        rejects(&["-i", "foo"]);
+        assert!(parse(&["-i", "-x", "sshd"]).is_ok());
+    }
+
+    #[test]
+    fn help_for_a_command() {
+        let parsed = parse(&["help", "list"]).unwrap();
+        assert_eq!(parsed.command, Command::List);
+        assert!(parsed.explicit_command);
+        assert!(parsed.flag("help"));
+
+        let parsed = parse(&["help"]).unwrap();
+        assert!(parsed.flag("help"));
+        assert!(!parsed.explicit_command);
+
+        let parsed = parse(&["watch", "--help"]).unwrap();
+        assert_eq!(parsed.command, Command::Watch);
+        assert!(parsed.flag("help"));
+
+        assert!(rejects(&["help", "nope"]).contains("nope"));
+    }
+
+    #[test]
+    fn typed_values_report_bad_input() {
+        let parsed = parse(&["--interval", "abc", "x"]).unwrap();
+        let err = parsed
+            .value("interval", |v| v.parse::<u64>().ok())
+            .unwrap_err();
+        assert!(err.to_string().contains("--interval"));
+        assert_eq!(
+            parsed.value("max", |v| v.parse::<u64>().ok()).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn short_flags_are_unique() {
+        for (idx, opt) in OPTIONS.iter().enumerate() {
+            assert!(
+                OPTIONS[idx + 1..].iter().all(|other| other.long != opt.long
+                    && (opt.short.is_none() || other.short != opt.short)),
+                "duplicate option --{}",
+                opt.long
+            );
+        }
+    }
+}
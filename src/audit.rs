@@ -0,0 +1,208 @@
+//! The audit log: one JSON line for every process grim signals, from the CLI,
+//! watch mode or the TUI, so a missing process can be traced back to a person.
+
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use grim::units::rfc3339;
+use grim::{KillOutcome, ProcessSnapshot, signal_name};
+use serde::{Deserialize, Serialize};
+use sysinfo::{PidExt, Signal, System, SystemExt};
+
+use crate::config::state_dir;
+
+/// One signalled process.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AuditEntry {
+    pub timestamp: String,
+    /// Who ran grim.
+    pub user: String,
+    /// The user behind `sudo`, when grim ran as root through it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sudo_user: Option<String>,
+    /// `cli`, `watch` or `tui`.
+    pub mode: String,
+    pub pid: u32,
+    pub name: String,
+    pub cmd: String,
+    #[serde(default)]
+    pub exe: String,
+    /// Who owned the process.
+    pub owner: String,
+    /// The signal sent, or `nice N` for a renice.
+    pub signal: String,
+    pub outcome: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+}
+
+/// Appends [`AuditEntry`] lines for one grim run.
+pub struct AuditLog {
+    path: Option<PathBuf>,
+    mode: &'static str,
+    user: String,
+    sudo_user: Option<String>,
+    reason: Option<String>,
+}
+
+impl AuditLog {
+    /// `$XDG_STATE_HOME/grim/audit.log`, falling back to `~/.local/state/grim/audit.log`.
+    pub fn default_path() -> Option<PathBuf> {
+        state_dir().map(|dir| dir.join("audit.log"))
+    }
+
+    /// A log for this run, written to `path` (or the default location).
+    pub fn new(
+        sys: &System,
+        path: Option<PathBuf>,
+        mode: &'static str,
+        reason: Option<String>,
+    ) -> Self {
+        let user = sysinfo::get_current_pid()
+            .ok()
+            .and_then(|pid| sys.process(pid))
+            .map(|proc| grim::process::owner_name(sys, proc))
+            .unwrap_or_else(|| "?".to_string());
+        Self {
+            path: path.or_else(Self::default_path),
+            mode,
+            user,
+            sudo_user: env::var("SUDO_USER").ok().filter(|user| !user.is_empty()),
+            reason,
+        }
+    }
+
+    /// Appends an entry for `process`. Protected processes were never signalled
+    /// and are not recorded.
+    pub fn record(
+        &self,
+        process: &ProcessSnapshot,
+        signal: Signal,
+        outcome: &KillOutcome,
+        rule: &str,
+    ) -> io::Result<()> {
+        self.record_action(process, &signal_name(signal), outcome, rule)
+    }
+
+    /// Like [`AuditLog::record`], for something other than a signal, e.g. `nice 10`.
+    pub fn record_action(
+        &self,
+        process: &ProcessSnapshot,
+        action: &str,
+        outcome: &KillOutcome,
+        rule: &str,
+    ) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if matches!(outcome, KillOutcome::Protected) {
+            return Ok(());
+        }
+        let entry = AuditEntry {
+            timestamp: rfc3339(SystemTime::now()),
+            user: self.user.clone(),
+            sudo_user: self.sudo_user.clone(),
+            mode: self.mode.to_string(),
+            pid: process.pid.as_u32(),
+            name: process.name.clone(),
+            cmd: process.cmd.clone(),
+            exe: process.exe.to_string_lossy().into_owned(),
+            owner: process.user.clone(),
+            signal: action.to_string(),
+            outcome: outcome.label().to_string(),
+            error: outcome.failure(),
+            reason: self.reason.clone(),
+            rule: (!rule.is_empty()).then(|| rule.to_string()),
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)
+    }
+}
+
+/// Every entry in the log at `path`, oldest first. Lines that do not parse
+/// (say, from a half-written entry) are skipped; a missing log is empty.
+pub fn read_entries(path: &Path) -> io::Result<Vec<AuditEntry>> {
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e),
+    };
+    let mut entries = vec![];
+    for line in BufReader::new(file).lines() {
+        if let Ok(entry) = serde_json::from_str(&line?) {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sysinfo::Pid;
+
+    fn snapshot() -> ProcessSnapshot {
+        ProcessSnapshot {
+            user: "www".to_string(),
+            ..ProcessSnapshot::new(Pid::from_u32(4242), "node")
+        }
+    }
+
+    #[test]
+    fn records_and_reads_back_entries() {
+        let dir = env::temp_dir().join(format!("grim-audit-test-{}", std::process::id()));
+        let path = dir.join("audit.log");
+        let _ = fs::remove_dir_all(&dir);
+
+        let log = AuditLog::new(
+            &System::new(),
+            Some(path.clone()),
+            "cli",
+            Some("runaway".to_string()),
+        );
+        log.record(&snapshot(), Signal::Term, &KillOutcome::Signalled, "")
+            .unwrap();
+        log.record(&snapshot(), Signal::Term, &KillOutcome::Protected, "")
+            .unwrap();
+        let denied = KillOutcome::Failed(io::Error::from(io::ErrorKind::PermissionDenied));
+        log.record(&snapshot(), Signal::Kill, &denied, "hogs")
+            .unwrap();
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{not json\n")
+            .unwrap();
+
+        let entries = read_entries(&path).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].pid, 4242);
+        assert_eq!(entries[0].owner, "www");
+        assert_eq!(entries[0].signal, "SIGTERM");
+        assert_eq!(entries[0].outcome, "signalled");
+        assert_eq!(entries[0].reason.as_deref(), Some("runaway"));
+        assert_eq!(entries[1].outcome, "failed");
+        assert!(entries[1].error.is_some());
+        assert_eq!(entries[1].rule.as_deref(), Some("hogs"));
+    }
+
+    #[test]
+    fn missing_log_is_empty() {
+        assert!(
+            read_entries(Path::new("/nonexistent/grim/audit.log"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,1831 @@
+//! The scripted command-line front-end.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use grim::process::process_tree;
+use grim::units::{format_duration, parse_duration, parse_size, rfc3339};
+use grim::{
+    Escalation, Filters, GrimError, KillOutcome, Killer, Match, MatchField, MatchMode, Matcher,
+    ProcessSnapshot, Protection, Target, parse_nice, parse_signal, resolve_user, set_nice,
+    signal_name,
+};
+use serde_json::{Value, json};
+use sysinfo::{Pid, PidExt, ProcessExt, ProcessRefreshKind, Signal, System, SystemExt, Uid};
+
+use crate::args::{Command, OPTIONS, ParsedArgs, parse_args};
+use crate::audit::{AuditLog, read_entries};
+use crate::cli_error::CliError;
+use crate::config::Config;
+use crate::daemon::{self, Daemon};
+use crate::frozen::FrozenList;
+use crate::rules::{Rule, RuleDefaults, RuleState, load_rules};
+use crate::tui::grim_interactive;
+
+/// How often the daemon checks for SIGTERM and SIGHUP between watch checks.
+const SIGNAL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// How often processes inside a grace period or wait are re-checked.
+const ESCALATION_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+pub fn grim_command(args: Vec<String>) -> Result<(), CliError> {
+    let args = parse_args(&args)?;
+    if args.flag("help") {
+        print_help(args.explicit_command.then_some(args.command));
+        return Ok(());
+    }
+    if args.command != Command::Daemon {
+        return run(&args, None);
+    }
+
+    // `json` holds every event until exit, which a service may never reach.
+    if args.value("output", OutputFormat::parse)? == Some(OutputFormat::Json) {
+        return Err(GrimError::BadArguments(
+            "grim daemon cannot use --output json; use ndjson to stream events".to_string(),
+        )
+        .into());
+    }
+    let path = |long| args.value(long, |v| Some(PathBuf::from(v)));
+    let daemon = Daemon::start(path("pid-file")?, path("log-file")?)?;
+    // A SIGHUP ends the current run once the new config and rules are known to
+    // load; the next run starts from them.
+    loop {
+        run(&args, Some(&daemon))?;
+        if !daemon::take_reload() {
+            return Ok(());
+        }
+    }
+}
+
+fn load_config(args: &ParsedArgs) -> Result<Config, CliError> {
+    if args.flag("no-config") {
+        Ok(Config::default())
+    } else {
+        Config::load(args.value("config", |v| Some(PathBuf::from(v)))?.as_deref())
+    }
+}
+
+fn run(args: &ParsedArgs, daemon: Option<&Daemon>) -> Result<(), CliError> {
+    let command = if args.flag("interactive") {
+        Command::Tui
+    } else {
+        args.command
+    };
+    let config = load_config(args)?;
+    if command == Command::Log {
+        return show_log(args, &config);
+    }
+
+    let mut sys = System::new_all();
+    sys.refresh_all();
+
+    // Command-line flags add to the config defaults; `--no-config` drops them.
+    // The daemon never prompts.
+    let force = args.flag("force") || config.defaults.force || daemon.is_some();
+    let kill_children = args.flag("kill-children") || config.defaults.kill_children;
+    let match_mode = match args.last_of(&["exact", "regex", "glob"]) {
+        Some("exact") => MatchMode::Exact,
+        Some("regex") => MatchMode::Regex,
+        Some("glob") => MatchMode::Glob,
+        _ if config.defaults.exact => MatchMode::Exact,
+        _ => MatchMode::Substring,
+    };
+    let mut match_fields: Vec<MatchField> = vec![];
+    for list in args.values("field") {
+        for field in list.split(',') {
+            match_fields.push(MatchField::parse(field).ok_or_else(|| {
+                GrimError::BadArguments(format!("Unknown match field: {}", field))
+            })?);
+        }
+    }
+    let case_sensitive = args.flag("case-sensitive");
+    let all = args.flag("all");
+    // Thresholds that every process passes would sneak `--all` past its filter check.
+    let cpu_above = args.value("cpu-above", |v| {
+        v.trim_end_matches('%')
+            .parse::<f32>()
+            .ok()
+            .filter(|pct| pct.is_finite() && *pct >= 0.0)
+    })?;
+    let mem_above = args.value("mem-above", |v| parse_size(v).filter(|bytes| *bytes > 0))?;
+    let sustain = args.value("for", parse_duration)?;
+    let older_than = args.value("older-than", parse_duration)?;
+    let younger_than = args.value("younger-than", parse_duration)?;
+    let output = args
+        .value("output", OutputFormat::parse)?
+        .unwrap_or(OutputFormat::Text);
+    let watch = matches!(command, Command::Watch | Command::Daemon) || args.flag("watch");
+    let dry_run = args.flag("dry-run");
+    let interval = args
+        .value("interval", |v| {
+            v.parse::<u64>().ok().filter(|secs| *secs > 0)
+        })?
+        .or(config.defaults.interval)
+        .unwrap_or(2);
+    let max_kills = args.value("max", |v| v.parse::<usize>().ok())?;
+    let timeout = args.value("timeout", |v| v.parse::<u64>().ok())?;
+    let signal_arg = args.value("signal", parse_signal)?;
+    let grace = args.value("grace", parse_duration)?;
+    let wait = args.value("wait", |v| parse_duration(v).filter(|wait| !wait.is_zero()))?;
+    let mut targets = args.targets.clone();
+    // `grim renice <target>... <niceness>`: the last positional is the nice value.
+    let nice = if command == Command::Renice {
+        let Some(value) = targets.pop() else {
+            return Err(GrimError::BadArguments(
+                "Usage: grim renice <target>... <niceness>".to_string(),
+            )
+            .into());
+        };
+        Some(parse_nice(&value).ok_or_else(|| {
+            GrimError::BadArguments(format!("Invalid niceness: {} (expected -20 to 19)", value))
+        })?)
+    } else {
+        None
+    };
+    targets.extend(args.values("port").iter().map(|port| format!(":{}", port)));
+
+    let signal = match command {
+        Command::Freeze => Signal::Stop,
+        Command::Thaw => Signal::Continue,
+        // With a grace period the first signal defaults to SIGTERM; SIGKILL follows on timeout.
+        _ => signal_arg.unwrap_or(if grace.is_some() {
+            Signal::Term
+        } else {
+            Signal::Kill
+        }),
+    };
+    // Past tense and participle for reporting, e.g. "Froze PID 123", "processes frozen".
+    let (verb, done) = match command {
+        Command::Freeze => ("Froze", "frozen"),
+        Command::Thaw => ("Thawed", "thawed"),
+        Command::Renice => ("Reniced", "reniced"),
+        _ => ("Killed", "killed"),
+    };
+    let mut frozen = FrozenList::load();
+    let frozen_changed = frozen.prune(&sys);
+
+    let mut excludes = args
+        .values("exclude")
+        .into_iter()
+        .map(|pattern| Matcher::parse(pattern, match_mode, &match_fields, case_sensitive))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(GrimError::BadArguments)?;
+    excludes.extend(config.protected.iter().cloned());
+
+    let reason = args.values("reason").pop().map(str::to_string);
+    if command == Command::Tui {
+        let audit = AuditLog::new(&sys, config.audit_log.clone(), "tui", reason);
+        return grim_interactive(grace, wait, excludes, &config, audit)
+            .map_err(|e| CliError::Terminal(e.to_string()));
+    }
+    let filters = Filters {
+        user: args
+            .values("user")
+            .pop()
+            .map(|user| resolve_user(&sys, user))
+            .transpose()
+            .map_err(GrimError::BadArguments)?,
+        cpu_above,
+        mem_above,
+        older_than,
+        younger_than,
+    };
+    let protection = Protection::new(&sys, excludes);
+    let rule_defaults = RuleDefaults {
+        signal: signal_arg,
+        grace,
+        wait,
+        kill_children,
+    };
+
+    if command == Command::Rules {
+        let [action, path] = targets.as_slice() else {
+            return Err(
+                GrimError::BadArguments("Usage: grim rules check <file>".to_string()).into(),
+            );
+        };
+        if action != "check" {
+            return Err(GrimError::BadArguments(format!(
+                "Unknown rules action: {} (expected check)",
+                action
+            ))
+            .into());
+        }
+        let rules = load_rules(Path::new(path), &sys, &rule_defaults)?;
+        thread::sleep(System::MINIMUM_CPU_UPDATE_INTERVAL);
+        sys.refresh_all();
+        return check_rules(&rules, &sys, &protection, &mut Reporter::new(output));
+    }
+
+    let mut rules = match args.values("rules").pop() {
+        Some(path) => {
+            if !targets.is_empty() || all || filters.is_restrictive() || sustain.is_some() {
+                return Err(GrimError::BadArguments(
+                    "--rules replaces targets and filters; put them in the rules file".to_string(),
+                )
+                .into());
+            }
+            load_rules(Path::new(path), &sys, &rule_defaults)?
+        }
+        None => {
+            if all && !filters.is_restrictive() {
+                return Err(GrimError::BadArguments(
+                    "--all needs a filter such as --user or --older-than".to_string(),
+                )
+                .into());
+            }
+            if sustain.is_some() && !filters.has_thresholds() {
+                return Err(GrimError::BadArguments(
+                    "--for needs --cpu-above or --mem-above".to_string(),
+                )
+                .into());
+            }
+            if sustain.is_some() && !watch {
+                return Err(GrimError::BadArguments(
+                    "--for needs --watch to track processes over time".to_string(),
+                )
+                .into());
+            }
+            // A bare `grim thaw` resumes everything grim froze and nobody resumed yet.
+            if command == Command::Thaw && targets.is_empty() && !all && !filters.is_restrictive() {
+                if frozen.processes.is_empty() {
+                    return Err(GrimError::NoMatches.into());
+                }
+                targets.extend(frozen.processes.iter().map(|p| p.pid.to_string()));
+            }
+            // Threshold-only runs target every process that crosses the limit.
+            let all = all || (targets.is_empty() && filters.has_thresholds());
+            if targets.is_empty() && !all {
+                let mut msg =
+                    "Missing targets for grim (PIDs, process names or :ports)".to_string();
+                if args.explicit_command {
+                    // `grim watch` used to mean "kill the watch utility".
+                    let name = command.name();
+                    msg.push_str(&format!(
+                        "; to target a process named '{}', use `grim kill {}` or `grim -- {}`",
+                        name, name, name
+                    ));
+                }
+                return Err(GrimError::BadArguments(msg).into());
+            }
+
+            let mut parsed_targets = vec![];
+            if all {
+                parsed_targets.push(("--all".to_string(), Target::All));
+            }
+            for target in &targets {
+                let parsed = Target::parse(target, match_mode, &match_fields, case_sensitive)
+                    .map_err(GrimError::BadArguments)?;
+                parsed_targets.push((target.clone(), parsed));
+            }
+            vec![Rule {
+                name: String::new(),
+                targets: parsed_targets,
+                filters,
+                sustain,
+                killer: Killer::new(signal).with_grace(grace).with_wait(wait),
+                kill_children,
+                max: None,
+                cooldown: None,
+                state: RuleState::default(),
+            }]
+        }
+    };
+    for rule in &mut rules {
+        rule.killer = rule.killer.clone().with_protection(protection.clone());
+    }
+
+    let start_time = Instant::now();
+    let mut total_matched = 0;
+    let mut tally = Tally {
+        killed: 0,
+        failed: 0,
+        denied: 0,
+        failures: vec![],
+        frozen,
+        frozen_changed,
+        audit_failed: false,
+    };
+    let mut iterations = 0;
+    let mut stop_reason = "done";
+    let mut reporter = Reporter::new(output);
+    if let Some(daemon) = daemon {
+        let log = daemon
+            .log_file
+            .as_ref()
+            .map(|path| {
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|e| CliError::Daemon(format!("{}: {}", path.display(), e)))
+            })
+            .transpose()?;
+        reporter = reporter.for_daemon(log);
+    }
+    let rules_file = args.values("rules").pop().map(Path::new);
+    let audit = AuditLog::new(
+        &sys,
+        config.audit_log.clone(),
+        if watch { "watch" } else { "cli" },
+        reason,
+    );
+
+    if rules.iter().any(|rule| rule.filters.cpu_above.is_some()) || command == Command::List {
+        // CPU usage is a delta between two refreshes, so give the first one something to compare.
+        thread::sleep(System::MINIMUM_CPU_UPDATE_INTERVAL);
+    }
+
+    if matches!(command, Command::List | Command::Tree) {
+        sys.refresh_all();
+        let matches = rules[0].matches(&sys, &protection, false);
+        show_matches(&sys, &protection, &matches, command, &mut reporter);
+        return if matches.is_empty() {
+            Err(GrimError::NoMatches.into())
+        } else {
+            Ok(())
+        };
+    }
+
+    let mut mass_limit = MassLimit {
+        limit: config.safety.mass_kill_limit,
+        yes_many: args.flag("yes-many"),
+        confirmed: 0,
+    };
+    let mut too_many = None;
+    let confirm_others = config.safety.confirm_other_users && !force;
+    let me = sysinfo::get_current_pid()
+        .ok()
+        .and_then(|pid| sys.process(pid))
+        .and_then(|proc| proc.user_id().cloned());
+
+    loop {
+        sys.refresh_all();
+
+        // PIDs already signalled this round, by another rule or as part of another match's tree.
+        let mut signalled: HashSet<Pid> = HashSet::new();
+        let mut round_matches = 0;
+        // The answer to the batch prompt holds until the next check.
+        let mut batch: Option<Batch> = None;
+        // Whether "all" also covers processes owned by other users.
+        let mut include_others: Option<bool> = None;
+        // Signalled processes still inside a grace period or wait, with their rule.
+        let mut escalation = Escalation::default();
+        let mut in_flight: HashMap<Pid, (usize, ProcessSnapshot)> = HashMap::new();
+        let mut aborted = false;
+        iterations += 1;
+
+        for (index, rule) in rules.iter_mut().enumerate() {
+            rule.track(&sys);
+            if !rule.is_active() {
+                continue;
+            }
+            let matches = rule.matches(&sys, &protection, true);
+            round_matches += matches.len();
+            total_matched += matches.len();
+            let action = Action::new(rule.killer.signal(), nice, verb);
+            let request = match nice {
+                Some(nice) => format!("Set nice {} on", nice),
+                None => format!("Send {} to", action.name),
+            };
+
+            let pending: Vec<&ProcessSnapshot> = matches
+                .iter()
+                .map(|m| &m.process)
+                .filter(|process| !signalled.contains(&process.pid))
+                .collect();
+            // Everything these matches would take down, trees included.
+            let mut affected: HashSet<Pid> = pending.iter().map(|process| process.pid).collect();
+            if rule.kill_children {
+                for process in &pending {
+                    let tree = protection.prune(&sys, process_tree(&sys, process.pid));
+                    affected.extend(tree.into_iter().map(|(pid, _, _)| pid));
+                }
+            }
+            affected.retain(|pid| !signalled.contains(pid));
+            let affected = affected.len();
+            // A short pattern can match half the system: make sure that is really meant,
+            // even with --force.
+            if !dry_run && mass_limit.needs_confirmation(affected) {
+                reporter.event(
+                    "too_many",
+                    tag_rule(
+                        json!({
+                            "matches": pending.len(),
+                            "affected": affected,
+                            "limit": mass_limit.limit,
+                        }),
+                        rule,
+                    ),
+                );
+                let count = if affected > pending.len() {
+                    format!(
+                        "{} processes match ({} with their descendants)",
+                        pending.len(),
+                        affected
+                    )
+                } else {
+                    format!("{} processes match", affected)
+                };
+                if daemon.is_some() {
+                    reporter.say(format!(
+                        "🛑 {}, more than the safety limit of {}; leaving them alone (see --yes-many)",
+                        count, mass_limit.limit
+                    ));
+                    continue;
+                }
+                reporter.say(format!(
+                    "\n🛑 {}, more than the safety limit of {}:",
+                    count, mass_limit.limit
+                ));
+                reporter.say(list_header());
+                for process in &pending {
+                    reporter.say(list_row(process));
+                }
+                reporter.prompt(format!(
+                    "⚠️  Type {} to {} all of them, anything else to stop: ",
+                    affected,
+                    if nice.is_some() { "renice" } else { "signal" }
+                ));
+                if !mass_limit.confirm(&mut io::stdin().lock(), affected) {
+                    reporter.say("🛑 Aborted.");
+                    stop_reason = "too_many";
+                    too_many = Some(affected);
+                    aborted = true;
+                    break;
+                }
+            }
+            if !force && !dry_run && batch.is_none() && pending.len() > 1 && !reporter.is_closed() {
+                reporter.say(format!("\n🔍 {} processes match:", pending.len()));
+                reporter.say(list_header());
+                for process in &pending {
+                    reporter.say(list_row(process));
+                }
+                let choice = ask_batch(
+                    &mut reporter,
+                    &mut io::stdin().lock(),
+                    &format!(
+                        "⚠️  {} [a]ll{}, [n]one, [s]elect individually or [q]uit? ",
+                        request,
+                        if rule.kill_children {
+                            " (with their trees)"
+                        } else {
+                            ""
+                        }
+                    ),
+                );
+                reporter.event(
+                    "batch_prompt",
+                    json!({ "matches": pending.len(), "choice": choice.label() }),
+                );
+                match choice {
+                    Batch::Quit => {
+                        reporter.say("🛑 Aborted.");
+                        stop_reason = "aborted";
+                        aborted = true;
+                        break;
+                    }
+                    Batch::None => {
+                        reporter.say(format!("⏭️  Skipping {} process(es)", pending.len()))
+                    }
+                    Batch::All => {
+                        let others = owned_by_others(pending.iter().copied(), me.as_ref());
+                        if confirm_others && !others.is_empty() {
+                            include_others = Some(ask_yes_no(
+                                &mut reporter,
+                                &mut io::stdin().lock(),
+                                &format!(
+                                    "⚠️  {} of them are owned by {}. Include those too? (y/N): ",
+                                    others.len(),
+                                    owners(&others)
+                                ),
+                            ));
+                        }
+                    }
+                    Batch::Select => {}
+                }
+                batch = Some(choice);
+            }
+            if batch == Some(Batch::None) {
+                continue;
+            }
+
+            for Match {
+                process,
+                matched_by,
+            } in &matches
+            {
+                let rule_in_flight = in_flight.values().filter(|(i, _)| *i == index).count();
+                if reporter.is_closed()
+                    || !rule.has_room(rule_in_flight)
+                    || daemon::shutdown_requested()
+                {
+                    break;
+                }
+                let pid = process.pid;
+                if signalled.contains(&pid) {
+                    continue;
+                }
+                // Skip the CPU refresh: measured over this short gap it would read as ~0%.
+                sys.refresh_process_specifics(pid, ProcessRefreshKind::new());
+                let Some(proc) = sys.process(pid) else {
+                    continue;
+                };
+                let process = ProcessSnapshot::capture(&sys, proc);
+                let parent_pid = process.parent.map(|p| p.as_u32()).unwrap_or(0);
+                let tree = protection.prune(&sys, process_tree(&sys, pid));
+
+                reporter.event(
+                    "match",
+                    tag_rule(match_json(&process, matched_by, &tree), rule),
+                );
+
+                reporter.say("\n🔍 Found process:");
+                reporter.say(format!("    PID:        {}", pid.as_u32()));
+                reporter.say(format!("    Name:       {}", process.name));
+                reporter.say(format!("    Cmd:        {}", process.cmd));
+                if !rule.name.is_empty() {
+                    reporter.say(format!("    Rule:       {}", rule.name));
+                }
+                reporter.say(format!("    Matched by: {}", matched_by.join(", ")));
+                reporter.say(format!("    User:       {}", process.user));
+                reporter.say(format!("    CPU usage:  {:.2}%", process.cpu));
+                reporter.say(format!(
+                    "    Memory:     {:.2} MB",
+                    process.memory as f64 / 1024.0 / 1024.0
+                ));
+                reporter.say(format!("    Uptime:     {}", format_duration(process.age)));
+                reporter.say(format!("    Parent PID: {}", parent_pid));
+                if let Some(since) = rule.over_since(pid) {
+                    reporter.say(format!(
+                        "    Over limit: {}",
+                        format_duration(since.elapsed())
+                    ));
+                }
+
+                if !tree.is_empty() {
+                    reporter.say(format!("⚠️  Has {} descendant(s):", tree.len()));
+                    for (cpid, cname, depth) in &tree {
+                        reporter.say(format!(
+                            "    {}↳ PID {} - {}",
+                            "  ".repeat(*depth),
+                            cpid.as_u32(),
+                            cname
+                        ));
+                    }
+                } else {
+                    reporter.say("    Child processes: (none)");
+                }
+
+                // Snapshot the whole group before signalling, while the processes still
+                // exist. Leaves first, so nothing gets reparented to init halfway through.
+                let mut group: Vec<ProcessSnapshot> = vec![];
+                if rule.kill_children {
+                    group.extend(
+                        tree.iter()
+                            .rev()
+                            .filter_map(|(cpid, _, _)| sys.process(*cpid))
+                            .map(|child| ProcessSnapshot::capture(&sys, child)),
+                    );
+                }
+                group.push(process.clone());
+
+                if dry_run {
+                    signalled.extend(group.iter().map(|member| member.pid));
+                    for member in &group {
+                        reporter.say(format!(
+                            "🧪 Would {} PID {} ({})",
+                            match nice {
+                                Some(nice) => format!("set nice {} on", nice),
+                                None => format!("send {} to", action.name),
+                            },
+                            member.pid.as_u32(),
+                            member.name
+                        ));
+                        let mut event = process_json(member);
+                        event["signal"] = json!(action.name);
+                        event["success"] = json!(true);
+                        event["dry_run"] = json!(true);
+                        event["outcome"] = json!("dry_run");
+                        reporter.event("kill", tag_rule(event, rule));
+                        tally.killed += 1;
+                        rule.record_kill();
+                    }
+                    continue;
+                }
+
+                if !force && batch != Some(Batch::All) {
+                    let confirmed = ask_yes_no(
+                        &mut reporter,
+                        &mut io::stdin().lock(),
+                        &format!(
+                            "⚠️  {} this process{} (matched by {})? (y/N): ",
+                            request,
+                            if rule.kill_children && !tree.is_empty() {
+                                " and its whole tree"
+                            } else {
+                                ""
+                            },
+                            matched_by.join(", ")
+                        ),
+                    );
+                    reporter.event(
+                        "prompt",
+                        json!({ "pid": pid.as_u32(), "name": process.name, "confirmed": confirmed }),
+                    );
+                    if !confirmed {
+                        reporter.say(format!("⏭️  Skipping PID {}", pid.as_u32()));
+                        continue;
+                    }
+                }
+
+                // Someone else's processes get a second confirmation, asked once for a
+                // batch and per match otherwise.
+                let others = owned_by_others(&group, me.as_ref());
+                if confirm_others && !others.is_empty() {
+                    let include = match (batch, include_others) {
+                        (Some(Batch::All), Some(include)) => include,
+                        _ => ask_yes_no(
+                            &mut reporter,
+                            &mut io::stdin().lock(),
+                            &format!(
+                                "⚠️  {} {} {} owned by {}. {} {} anyway? (y/N): ",
+                                if others.len() == 1 { "PID" } else { "PIDs" },
+                                others
+                                    .iter()
+                                    .map(|other| other.pid.as_u32().to_string())
+                                    .collect::<Vec<_>>()
+                                    .join(", "),
+                                if others.len() == 1 { "is" } else { "are" },
+                                owners(&others),
+                                request,
+                                if others.len() == 1 { "it" } else { "them" }
+                            ),
+                        ),
+                    };
+                    if !include {
+                        let skipped: HashSet<Pid> = others.iter().map(|other| other.pid).collect();
+                        for other in &others {
+                            reporter.say(format!(
+                                "⏭️  Skipping PID {} ({}), owned by {}",
+                                other.pid.as_u32(),
+                                other.name,
+                                other.user
+                            ));
+                        }
+                        group.retain(|member| !skipped.contains(&member.pid));
+                        if group.is_empty() {
+                            continue;
+                        }
+                    }
+                }
+
+                signalled.extend(group.iter().map(|member| member.pid));
+                let outcomes = match nice {
+                    Some(nice) => group
+                        .iter()
+                        .map(|member| (member.pid, renice(&mut sys, member, nice)))
+                        .collect(),
+                    // The prompt may have sat for minutes: the killer checks that each
+                    // PID still belongs to the process shown before signalling it.
+                    None => {
+                        let (outcomes, pending) = rule.killer.start(&mut sys, &group);
+                        escalation.merge(pending);
+                        outcomes
+                    }
+                };
+                let mut outcomes: HashMap<Pid, KillOutcome> = outcomes.into_iter().collect();
+                for member in group {
+                    match outcomes.remove(&member.pid) {
+                        Some(outcome) => {
+                            tally.record(&mut reporter, &audit, rule, &action, &member, outcome)
+                        }
+                        None => {
+                            in_flight.insert(member.pid, (index, member));
+                        }
+                    }
+                }
+            }
+        }
+
+        // Every match of this check waits out its grace period or wait at the same time.
+        loop {
+            for (pid, outcome) in escalation.poll(&mut sys) {
+                if let Some((index, member)) = in_flight.remove(&pid) {
+                    let rule = &mut rules[index];
+                    let action = Action::new(rule.killer.signal(), nice, verb);
+                    tally.record(&mut reporter, &audit, rule, &action, &member, outcome);
+                }
+            }
+            if escalation.is_empty() {
+                break;
+            }
+            thread::sleep(ESCALATION_POLL_INTERVAL);
+        }
+        if aborted {
+            break;
+        }
+
+        reporter.event(
+            "iteration",
+            json!({
+                "iteration": iterations,
+                "matches": round_matches,
+                "total_killed": tally.killed,
+            }),
+        );
+
+        if let Some(max) = max_kills
+            && tally.killed >= max
+        {
+            reporter.say(format!("🎉 Reached max kill count ({}). Exiting.", max));
+            stop_reason = "max_kills";
+            break;
+        }
+
+        if let Some(timeout_secs) = timeout
+            && start_time.elapsed().as_secs() >= timeout_secs
+        {
+            reporter.say(format!("⏰ Timeout of {}s reached. Exiting.", timeout_secs));
+            stop_reason = "timeout";
+            break;
+        }
+
+        if reporter.is_closed() {
+            stop_reason = "output_closed";
+            break;
+        }
+
+        if !watch {
+            break;
+        }
+
+        if daemon.is_some() {
+            let wake = Instant::now() + Duration::from_secs(interval);
+            while Instant::now() < wake
+                && !daemon::shutdown_requested()
+                && !daemon::reload_requested()
+            {
+                thread::sleep(SIGNAL_POLL_INTERVAL);
+            }
+        } else {
+            for sec in (1..=interval).rev() {
+                if reporter.is_text() {
+                    reporter.write(format!("\r⏳ Checking again in {}... ", sec));
+                }
+                thread::sleep(Duration::from_secs(1));
+            }
+            reporter.say("");
+        }
+
+        if daemon::shutdown_requested() {
+            reporter.say("🛑 Shutting down.");
+            stop_reason = "shutdown";
+            break;
+        }
+        if daemon::reload_requested() {
+            // Keep the current settings unless the new files load cleanly.
+            let reloaded = load_config(args).and_then(|_| match rules_file {
+                Some(path) => load_rules(path, &sys, &rule_defaults).map(|_| ()),
+                None => Ok(()),
+            });
+            match reloaded {
+                Ok(()) => {
+                    reporter.say("🔄 Reloading config and rules.");
+                    stop_reason = "reload";
+                    break;
+                }
+                Err(e) => {
+                    daemon::take_reload();
+                    reporter.say(format!(
+                        "⚠️  Reload failed, keeping current settings: {}",
+                        e
+                    ));
+                }
+            }
+        }
+    }
+
+    if tally.frozen_changed
+        && let Err(e) = tally.frozen.save()
+    {
+        eprintln!("grim: cannot save the list of frozen processes: {}", e);
+    }
+
+    if !tally.failures.is_empty() && stop_reason != "reload" {
+        reporter.say(format!(
+            "\n❌ {} process(es) not {}:",
+            tally.failures.len(),
+            done
+        ));
+        for (pid, name, reason) in &tally.failures {
+            reporter.say(format!("    PID {} ({}): {}", pid, name, reason));
+        }
+    }
+
+    if stop_reason == "reload" {
+        // The next run carries on; its summary covers the rest.
+    } else if dry_run {
+        reporter.say(format!(
+            "🎯 Finished (dry run). Processes that would be {}: {}",
+            done, tally.killed
+        ));
+    } else {
+        reporter.say(format!(
+            "🎯 Finished. Total processes {}: {}",
+            done, tally.killed
+        ));
+    }
+    reporter.finish(json!({
+        "total_killed": tally.killed,
+        "total_matched": total_matched,
+        "failed": tally.failed,
+        "failures": tally
+            .failures
+            .iter()
+            .map(|(pid, name, reason)| json!({ "pid": pid, "name": name, "error": reason }))
+            .collect::<Vec<_>>(),
+        "iterations": iterations,
+        "dry_run": dry_run,
+        "reason": stop_reason,
+    }));
+
+    if let Some(matched) = too_many {
+        Err(CliError::TooManyMatches {
+            matched,
+            limit: mass_limit.limit,
+        })
+    } else if matches!(stop_reason, "shutdown" | "reload") {
+        // A service that was asked to stop did its job, whatever it found.
+        Ok(())
+    } else if total_matched == 0 {
+        Err(GrimError::NoMatches.into())
+    } else if tally.failed > 0 && tally.killed == 0 && tally.denied == tally.failed {
+        Err(GrimError::PermissionDenied {
+            denied: tally.denied,
+        }
+        .into())
+    } else if tally.failed > 0 {
+        Err(GrimError::PartialFailure {
+            killed: tally.killed,
+            failed: tally.failed,
+        }
+        .into())
+    } else if tally.killed == 0 {
+        Err(GrimError::NothingKilled {
+            matched: total_matched,
+        }
+        .into())
+    } else {
+        Ok(())
+    }
+}
+
+/// What a run does to each matched process, for messages, events and the audit log.
+struct Action {
+    signal: Signal,
+    nice: Option<i32>,
+    /// The signal name, or `nice N` for a renice.
+    name: String,
+    /// Past tense for messages, e.g. "Froze".
+    verb: &'static str,
+}
+
+impl Action {
+    fn new(signal: Signal, nice: Option<i32>, verb: &'static str) -> Self {
+        let name = match nice {
+            Some(nice) => format!("nice {}", nice),
+            None => signal_name(signal),
+        };
+        Self {
+            signal,
+            nice,
+            name,
+            verb,
+        }
+    }
+}
+
+/// Totals for the summary at the end of a run, updated as outcomes come in.
+struct Tally {
+    killed: usize,
+    failed: usize,
+    denied: usize,
+    /// Every process that was not dealt with, and why.
+    failures: Vec<(u32, String, String)>,
+    frozen: FrozenList,
+    frozen_changed: bool,
+    audit_failed: bool,
+}
+
+impl Tally {
+    /// Reports what happened to `member`, writes it to the audit log and counts it.
+    fn record(
+        &mut self,
+        reporter: &mut Reporter,
+        audit: &AuditLog,
+        rule: &mut Rule,
+        action: &Action,
+        member: &ProcessSnapshot,
+        outcome: KillOutcome,
+    ) {
+        let mut event = process_json(member);
+        event["signal"] = json!(action.name);
+        event["success"] = json!(outcome.is_success());
+        event["dry_run"] = json!(false);
+        event["outcome"] = json!(outcome.label());
+        if let Err(e) = audit.record_action(member, &action.name, &outcome, &rule.name)
+            && !self.audit_failed
+        {
+            // Warn once; a broken log must not stop the kills themselves.
+            eprintln!("grim: cannot write audit log: {}", e);
+            self.audit_failed = true;
+        }
+        if outcome.is_success() {
+            self.killed += 1;
+            rule.record_kill();
+            // Anything else that ends a frozen process is pruned on the next run.
+            match action.signal {
+                Signal::Stop => self.frozen.add(member),
+                Signal::Continue => self.frozen.remove(member.pid),
+                _ => {}
+            }
+            self.frozen_changed |= matches!(action.signal, Signal::Stop | Signal::Continue);
+        }
+        match &outcome {
+            KillOutcome::Signalled => {
+                reporter.say(format!(
+                    "✅ {} PID {} ({}) {} {}",
+                    action.verb,
+                    member.pid.as_u32(),
+                    member.name,
+                    if action.nice.is_some() { "to" } else { "with" },
+                    action.name
+                ));
+            }
+            KillOutcome::Exited => {
+                reporter.say(format!(
+                    "✅ PID {} ({}) exited cleanly after {}",
+                    member.pid.as_u32(),
+                    member.name,
+                    signal_name(action.signal)
+                ));
+            }
+            KillOutcome::ForceKilled => {
+                reporter.say(format!(
+                    "💀 PID {} ({}) still alive after {}, force-killed with SIGKILL",
+                    member.pid.as_u32(),
+                    member.name,
+                    format_duration(rule.killer.grace().unwrap_or_default())
+                ));
+            }
+            KillOutcome::Protected => {
+                reporter.say(format!(
+                    "🛡️  PID {} ({}) is protected, leaving it alone",
+                    member.pid.as_u32(),
+                    member.name
+                ));
+            }
+            KillOutcome::Zombie => {
+                reporter.say(format!(
+                    "🧟 PID {} ({}) died but is a zombie until its parent (PID {}) reaps it",
+                    member.pid.as_u32(),
+                    member.name,
+                    member.parent.map(|p| p.as_u32()).unwrap_or(0)
+                ));
+            }
+            KillOutcome::Replaced => {
+                reporter.say(format!(
+                    "🔀 PID {} is no longer {}: the PID was reused, leaving it alone",
+                    member.pid.as_u32(),
+                    member.name
+                ));
+            }
+            KillOutcome::AlreadyGone => {
+                reporter.say(format!(
+                    "👻 PID {} ({}) was already gone",
+                    member.pid.as_u32(),
+                    member.name
+                ));
+            }
+            KillOutcome::StillAlive(wait) => {
+                reporter.say(format!(
+                    "⏳ PID {} ({}) got {} but is still alive after {}",
+                    member.pid.as_u32(),
+                    member.name,
+                    action.name,
+                    format_duration(*wait)
+                ));
+            }
+            KillOutcome::Failed(e) => {
+                reporter.say(format!(
+                    "❌ Failed to {} PID {} ({}): {}",
+                    if action.nice.is_some() {
+                        "renice"
+                    } else {
+                        "signal"
+                    },
+                    member.pid.as_u32(),
+                    member.name,
+                    e
+                ));
+                if e.kind() == io::ErrorKind::PermissionDenied {
+                    self.denied += 1;
+                }
+            }
+        }
+        if let Some(reason) = outcome.failure() {
+            event["error"] = json!(reason);
+            self.failed += 1;
+            self.failures
+                .push((member.pid.as_u32(), member.name.clone(), reason));
+        }
+        reporter.event("kill", tag_rule(event, rule));
+    }
+}
+
+/// The answer to the prompt shown when several processes match at once.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Batch {
+    All,
+    None,
+    Select,
+    Quit,
+}
+
+impl Batch {
+    fn label(self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::None => "none",
+            Self::Select => "select",
+            Self::Quit => "quit",
+        }
+    }
+
+    /// An answer to the batch prompt, or `None` if it is not one.
+    fn parse(answer: &str) -> Option<Self> {
+        match answer.trim().to_lowercase().as_str() {
+            "a" | "all" => Some(Self::All),
+            "n" | "none" => Some(Self::None),
+            "s" | "select" => Some(Self::Select),
+            "q" | "quit" => Some(Self::Quit),
+            _ => None,
+        }
+    }
+}
+
+/// Asks `question` until it gets a/n/s/q. End of input counts as "none".
+fn ask_batch(reporter: &mut Reporter, input: &mut impl BufRead, question: &str) -> Batch {
+    loop {
+        reporter.prompt(question);
+        let mut line = String::new();
+        if input.read_line(&mut line).unwrap_or(0) == 0 {
+            return Batch::None;
+        }
+        if let Some(choice) = Batch::parse(&line) {
+            return choice;
+        }
+    }
+}
+
+/// Asks a yes/no question; anything but yes, including end of input, means no.
+fn ask_yes_no(reporter: &mut Reporter, input: &mut impl BufRead, question: &str) -> bool {
+    reporter.prompt(question);
+    let mut line = String::new();
+    input.read_line(&mut line).unwrap_or(0);
+    is_yes(&line)
+}
+
+fn is_yes(answer: &str) -> bool {
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// The processes owned by anyone other than the user `me` grim runs as; all of
+/// them when that is unknown.
+fn owned_by_others<'a>(
+    processes: impl IntoIterator<Item = &'a ProcessSnapshot>,
+    me: Option<&Uid>,
+) -> Vec<&'a ProcessSnapshot> {
+    processes
+        .into_iter()
+        .filter(|process| me.is_none_or(|me| process.uid.as_ref() != Some(me)))
+        .collect()
+}
+
+/// The mass-kill safety limit: acting on more processes than this needs
+/// `--yes-many` or the count typed back.
+struct MassLimit {
+    /// 0 disables the check.
+    limit: usize,
+    yes_many: bool,
+    /// The largest count typed back so far; anything up to it needs no new confirmation.
+    confirmed: usize,
+}
+
+impl MassLimit {
+    fn needs_confirmation(&self, count: usize) -> bool {
+        !self.yes_many && self.limit > 0 && count > self.limit.max(self.confirmed)
+    }
+
+    /// Reads a line from `input` and checks that it is `count`. End of input or a
+    /// read error counts as a refusal.
+    fn confirm(&mut self, input: &mut impl BufRead, count: usize) -> bool {
+        let mut line = String::new();
+        input.read_line(&mut line).unwrap_or(0);
+        let confirmed = line.trim() == count.to_string();
+        if confirmed {
+            self.confirmed = count;
+        }
+        confirmed
+    }
+}
+
+/// The distinct owners of `processes`, for prompts.
+fn owners(processes: &[&ProcessSnapshot]) -> String {
+    let mut users: Vec<&str> = processes.iter().map(|p| p.user.as_str()).collect();
+    users.sort_unstable();
+    users.dedup();
+    users.join(", ")
+}
+
+/// Sets the nice value of `process`, unless its PID went to another process since
+/// it matched.
+fn renice(sys: &mut System, process: &ProcessSnapshot, nice: i32) -> KillOutcome {
+    if !sys.refresh_process_specifics(process.pid, ProcessRefreshKind::new()) {
+        return KillOutcome::AlreadyGone;
+    }
+    match sys.process(process.pid) {
+        Some(proc) if !process.is_same_process(proc) => KillOutcome::Replaced,
+        Some(_) => match set_nice(process.pid, nice) {
+            Ok(()) => KillOutcome::Signalled,
+            Err(e) => KillOutcome::Failed(e),
+        },
+        None => KillOutcome::AlreadyGone,
+    }
+}
+
+/// How `grim_command` reports progress on stdout.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum OutputFormat {
+    /// Human-readable lines.
+    Text,
+    /// A single JSON object with every event and the summary, printed at the end.
+    Json,
+    /// One JSON event per line as it happens, ending with the summary.
+    Ndjson,
+}
+
+impl OutputFormat {
+    fn parse(input: &str) -> Option<Self> {
+        match input.trim().to_lowercase().as_str() {
+            "text" => Some(Self::Text),
+            "json" => Some(Self::Json),
+            "ndjson" | "jsonl" => Some(Self::Ndjson),
+            _ => None,
+        }
+    }
+}
+
+/// Routes CLI output either to plain text or to structured JSON events.
+struct Reporter {
+    format: OutputFormat,
+    events: Vec<Value>,
+    /// Set once stdout is gone (e.g. `grim ... | head`); nothing more is written.
+    closed: bool,
+    /// Daemon log file, written instead of stdout.
+    log: Option<File>,
+    /// Prefix text lines with the time, for logs.
+    timestamps: bool,
+}
+
+impl Reporter {
+    fn new(format: OutputFormat) -> Self {
+        Self {
+            format,
+            events: vec![],
+            closed: false,
+            log: None,
+            timestamps: false,
+        }
+    }
+
+    /// Daemon output: timestamped lines, appended to `log` if given instead of stdout.
+    fn for_daemon(mut self, log: Option<File>) -> Self {
+        self.log = log;
+        self.timestamps = true;
+        self
+    }
+
+    fn is_text(&self) -> bool {
+        self.format == OutputFormat::Text
+    }
+
+    /// Whether the reader closed stdout, in which case the run should wind down.
+    fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Writes and flushes `text` on stdout. A closed pipe marks the reporter closed
+    /// instead of panicking the way `println!` would.
+    fn write(&mut self, text: impl std::fmt::Display) {
+        if self.closed {
+            return;
+        }
+        let result = match &mut self.log {
+            Some(log) => write!(log, "{}", text),
+            None => {
+                let mut stdout = io::stdout().lock();
+                write!(stdout, "{}", text).and_then(|()| stdout.flush())
+            }
+        };
+        if let Err(e) = result {
+            if e.kind() != io::ErrorKind::BrokenPipe {
+                eprintln!("grim: cannot write output: {}", e);
+            }
+            self.closed = true;
+        }
+    }
+
+    /// Prints a human-readable line; ignored in JSON modes.
+    fn say(&mut self, line: impl std::fmt::Display) {
+        if !self.is_text() {
+            return;
+        }
+        if self.timestamps {
+            // Log lines stand alone: no blank spacer lines, each one stamped.
+            let line = line.to_string();
+            let line = line.trim_start_matches('\n');
+            if !line.is_empty() {
+                self.write(format!("{} {}\n", rfc3339(SystemTime::now()), line));
+            }
+        } else {
+            self.write(format!("{}\n", line));
+        }
+    }
+
+    /// Asks a question without a trailing newline. JSON modes keep stdout clean by
+    /// prompting on stderr.
+    fn prompt(&mut self, question: impl std::fmt::Display) {
+        if self.is_text() {
+            self.write(question);
+        } else {
+            eprint!("{}", question);
+        }
+    }
+
+    /// Records a structured event; ignored in text mode.
+    fn event(&mut self, kind: &str, fields: Value) {
+        if self.is_text() {
+            return;
+        }
+        let mut event = json!({ "event": kind, "timestamp": rfc3339(SystemTime::now()) });
+        if let (Some(event), Value::Object(fields)) = (event.as_object_mut(), fields) {
+            event.extend(fields);
+        }
+        match self.format {
+            OutputFormat::Ndjson => self.write(format!("{}\n", event)),
+            _ => self.events.push(event),
+        }
+    }
+
+    /// Emits the final summary object.
+    fn finish(&mut self, summary: Value) {
+        match self.format {
+            OutputFormat::Text => {}
+            OutputFormat::Ndjson => self.event("summary", summary),
+            OutputFormat::Json => {
+                let events = std::mem::take(&mut self.events);
+                self.write(format!(
+                    "{}\n",
+                    json!({ "events": events, "summary": summary })
+                ));
+            }
+        }
+    }
+}
+
+/// A JSON match event for `process` and its (already pruned) descendant tree.
+fn match_json(
+    process: &ProcessSnapshot,
+    matched_by: &[String],
+    tree: &[(Pid, String, usize)],
+) -> Value {
+    let mut event = process_json(process);
+    event["user"] = json!(process.user);
+    event["parent_pid"] = json!(process.parent.map(|p| p.as_u32()).unwrap_or(0));
+    event["uptime_secs"] = json!(process.age.as_secs());
+    event["matched_by"] = json!(matched_by);
+    event["descendants"] = json!(
+        tree.iter()
+            .map(|(cpid, _, _)| cpid.as_u32())
+            .collect::<Vec<_>>()
+    );
+    event
+}
+
+/// Output for `grim list` (a table) and `grim tree` (each match with its descendants).
+fn show_matches(
+    sys: &System,
+    protection: &Protection,
+    matches: &[Match],
+    command: Command,
+    reporter: &mut Reporter,
+) {
+    if command == Command::List && !matches.is_empty() {
+        reporter.say(list_header());
+    }
+    for Match {
+        process,
+        matched_by,
+    } in matches
+    {
+        let tree = protection.prune(sys, process_tree(sys, process.pid));
+        let mut event = match_json(process, matched_by, &tree);
+        match command {
+            Command::Tree => {
+                event["tree"] = json!(
+                    tree.iter()
+                        .map(|(cpid, cname, depth)| json!({
+                            "pid": cpid.as_u32(),
+                            "name": cname,
+                            "depth": depth,
+                        }))
+                        .collect::<Vec<_>>()
+                );
+                reporter.say(format!(
+                    "\n🌳 PID {} - {} (matched by {})",
+                    process.pid.as_u32(),
+                    process.name,
+                    matched_by.join(", ")
+                ));
+                for (cpid, cname, depth) in &tree {
+                    reporter.say(format!(
+                        "    {}↳ PID {} - {}",
+                        "  ".repeat(*depth),
+                        cpid.as_u32(),
+                        cname
+                    ));
+                }
+            }
+            _ => reporter.say(list_row(process)),
+        }
+        reporter.event("match", event);
+    }
+
+    reporter.say(format!("\n🎯 {} matching process(es)", matches.len()));
+    reporter.finish(json!({ "total_matched": matches.len() }));
+}
+
+fn list_header() -> String {
+    format!(
+        "{:>7}  {:<12} {:>6} {:>10} {:>9}  {}",
+        "PID", "USER", "CPU%", "MEM", "UPTIME", "COMMAND"
+    )
+}
+
+/// One line of the `grim list` table.
+fn list_row(process: &ProcessSnapshot) -> String {
+    format!(
+        "{:>7}  {:<12} {:>6.1} {:>7.1} MB {:>9}  {}",
+        process.pid.as_u32(),
+        process.user,
+        process.cpu,
+        process.memory as f64 / 1024.0 / 1024.0,
+        format_duration(process.age),
+        if process.cmd.is_empty() {
+            &process.name
+        } else {
+            &process.cmd
+        }
+    )
+}
+
+/// `grim rules check`: what every rule would hit in the current snapshot.
+/// Nothing is signalled, and `for` durations are not waited out.
+fn check_rules(
+    rules: &[Rule],
+    sys: &System,
+    protection: &Protection,
+    reporter: &mut Reporter,
+) -> Result<(), CliError> {
+    let mut total = 0;
+    for rule in rules {
+        let matches = rule.matches(sys, protection, false);
+        total += matches.len();
+        reporter.say(format!(
+            "\n📜 {} — {} process(es) → {}",
+            rule.name,
+            matches.len(),
+            rule.describe_action()
+        ));
+        if !matches.is_empty() {
+            reporter.say(list_header());
+        }
+        for Match {
+            process,
+            matched_by,
+        } in &matches
+        {
+            reporter.say(list_row(process));
+            reporter.event(
+                "match",
+                tag_rule(match_json(process, matched_by, &[]), rule),
+            );
+        }
+        reporter.event(
+            "rule",
+            json!({
+                "rule": rule.name,
+                "matches": matches.len(),
+                "action": rule.describe_action(),
+            }),
+        );
+    }
+    reporter.say(format!(
+        "\n🎯 {} rule(s), {} matching process(es)",
+        rules.len(),
+        total
+    ));
+    reporter.finish(json!({ "rules": rules.len(), "total_matched": total }));
+    if total == 0 {
+        Err(GrimError::NoMatches.into())
+    } else {
+        Ok(())
+    }
+}
+
+/// `grim log`: audit entries, oldest first, narrowed down by targets (PIDs or
+/// patterns), `--since`, `--by` and `--user`.
+fn show_log(args: &ParsedArgs, config: &Config) -> Result<(), CliError> {
+    let output = args
+        .value("output", OutputFormat::parse)?
+        .unwrap_or(OutputFormat::Text);
+    let cutoff = args
+        .value("since", parse_duration)?
+        .and_then(|since| SystemTime::now().checked_sub(since))
+        .map(rfc3339);
+    let by = args.values("by").pop();
+    let owner = args.values("user").pop();
+    let targets = args
+        .targets
+        .iter()
+        .map(
+            |target| match Target::parse(target, MatchMode::Substring, &[], false)? {
+                // The log does not record sockets.
+                Target::Port(_) => Err(format!("grim log cannot filter by port: {}", target)),
+                target => Ok(target),
+            },
+        )
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(GrimError::BadArguments)?;
+
+    let path = config
+        .audit_log
+        .clone()
+        .or_else(AuditLog::default_path)
+        .ok_or_else(|| CliError::AuditLog("(set XDG_STATE_HOME or HOME)".to_string()))?;
+    let entries = read_entries(&path)
+        .map_err(|e| CliError::AuditLog(format!("{}: {}", path.display(), e)))?;
+
+    let mut reporter = Reporter::new(output);
+    let mut shown = 0;
+    for entry in &entries {
+        // Timestamps are fixed-width UTC, so they compare as strings.
+        if cutoff
+            .as_ref()
+            .is_some_and(|cutoff| entry.timestamp < *cutoff)
+            || by.is_some_and(|by| entry.user != by && entry.sudo_user.as_deref() != Some(by))
+            || owner.is_some_and(|owner| entry.owner != owner)
+        {
+            continue;
+        }
+        let selected = targets.is_empty()
+            || targets.iter().any(|target| match target {
+                Target::Pid(pid) => pid.as_u32() == entry.pid,
+                Target::Pattern(matcher) => matcher.is_match_with(|field| match field {
+                    MatchField::Name => entry.name.clone(),
+                    MatchField::Cmd => entry.cmd.clone(),
+                    MatchField::Exe => entry.exe.clone(),
+                }),
+                Target::Port(_) | Target::All => true,
+            });
+        if !selected {
+            continue;
+        }
+
+        if shown == 0 {
+            reporter.say(format!(
+                "{:<24}  {:<14} {:<5} {:<8} {:<12} {:>7}  {:<10} {}",
+                "TIME", "BY", "MODE", "SIGNAL", "OUTCOME", "PID", "OWNER", "COMMAND"
+            ));
+        }
+        shown += 1;
+        let by = match &entry.sudo_user {
+            Some(sudo_user) => format!("{} (sudo)", sudo_user),
+            None => entry.user.clone(),
+        };
+        let mut line = format!(
+            "{:<24}  {:<14} {:<5} {:<8} {:<12} {:>7}  {:<10} {}",
+            entry.timestamp,
+            by,
+            entry.mode,
+            entry.signal,
+            entry.outcome,
+            entry.pid,
+            entry.owner,
+            if entry.cmd.is_empty() {
+                &entry.name
+            } else {
+                &entry.cmd
+            }
+        );
+        if let Some(reason) = &entry.reason {
+            line.push_str(&format!("  # {}", reason));
+        }
+        reporter.say(line);
+        reporter.event("entry", json!(entry));
+    }
+
+    reporter.say(format!(
+        "\n📜 {} entr{} in {}",
+        shown,
+        if shown == 1 { "y" } else { "ies" },
+        path.display()
+    ));
+    reporter.finish(json!({ "total": shown, "path": path }));
+    if shown == 0 {
+        Err(GrimError::NoMatches.into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Adds the rule's name to a JSON event when running from a rules file.
+fn tag_rule(mut event: Value, rule: &Rule) -> Value {
+    if !rule.name.is_empty() {
+        event["rule"] = json!(rule.name);
+    }
+    event
+}
+
+/// The per-process fields shared by JSON match and kill events.
+fn process_json(process: &ProcessSnapshot) -> Value {
+    json!({
+        "pid": process.pid.as_u32(),
+        "name": process.name,
+        "cmd": process.cmd,
+        "cpu": process.cpu,
+        "memory": process.memory,
+    })
+}
+
+/// Prints help for one subcommand, or for grim as a whole. Commands and options
+/// come from the tables in `args`, so new ones show up here automatically.
+fn print_help(command: Option<Command>) {
+    // ANSI helpers
+    const RESET: &str = "\x1b[0m";
+    const BOLD: &str = "\x1b[1m";
+
+    const TITLE: &str = "\x1b[38;5;213m"; // pink/purple
+    const SECTION: &str = "\x1b[38;5;81m"; // cyan
+    const OPTION: &str = "\x1b[38;5;214m"; // orange
+    const ARG: &str = "\x1b[38;5;150m"; // green
+    const DESC: &str = "\x1b[38;5;250m"; // light gray
+
+    const BG_OPTION: &str = "\x1b[48;5;235m"; // dark background
+
+    println!();
+    match command {
+        Some(command) => {
+            println!(
+                "{TITLE}{BOLD}grim {}{RESET}{DESC} — {}{RESET}",
+                command.name(),
+                command.about()
+            );
+            println!();
+            println!("{SECTION}{BOLD}USAGE:{RESET}");
+            if command == Command::Tui {
+                println!("  {ARG}grim tui{RESET} {OPTION}[OPTIONS]{RESET}");
+            } else if command == Command::Renice {
+                println!(
+                    "  {ARG}grim renice{RESET} {OPTION}[OPTIONS]{RESET} {ARG}<TARGET>... <NICENESS>{RESET}"
+                );
+            } else {
+                println!(
+                    "  {ARG}grim {}{RESET} {OPTION}[OPTIONS]{RESET} {ARG}<TARGET>...{RESET}",
+                    command.name()
+                );
+            }
+        }
+        None => {
+            println!(
+                "{TITLE}{BOLD}grim{RESET}{DESC} — interactive and scripted process terminator{RESET}"
+            );
+            println!();
+            println!("{SECTION}{BOLD}USAGE:{RESET}");
+            println!(
+                "  {ARG}grim{RESET} {OPTION}[COMMAND] [OPTIONS]{RESET} {ARG}<TARGET>...{RESET}"
+            );
+            println!();
+            println!("{SECTION}{BOLD}COMMANDS:{RESET}");
+            for command in Command::ALL {
+                println!(
+                    "  {ARG}{:<8}{RESET} {DESC}{}{RESET}",
+                    command.name(),
+                    command.about()
+                );
+            }
+            println!(
+                "  {ARG}{:<8}{RESET} {DESC}Print help for a command{RESET}",
+                "help"
+            );
+        }
+    }
+
+    if command != Some(Command::Tui) {
+        println!();
+        println!("{SECTION}{BOLD}TARGETS:{RESET}");
+        for (target, desc) in [
+            ("PID", "Kill a specific process by PID"),
+            ("NAME", "Match process name or command line"),
+            ("re:EXPR", "Match a regular expression"),
+            ("glob:PATTERN", "Match a shell glob"),
+            ("exact:NAME", "Match the process name exactly"),
+            (":PORT[/tcp|udp]", "Kill whatever holds a local port"),
+            ("-- TARGET...", "Treat everything after -- as targets"),
+        ] {
+            println!("  {ARG}{:<22}{RESET} {DESC}{}{RESET}", target, desc);
+        }
+    }
+
+    let shown: Vec<_> = OPTIONS
+        .iter()
+        .filter(|opt| command.is_none_or(|command| opt.applies_to(command)))
+        .collect();
+    let label = |long: &str, value: Option<&str>| {
+        let value = value
+            .map(|value| format!("<{}>", value))
+            .unwrap_or_default();
+        (format!("--{} ", long), value)
+    };
+    let width = shown
+        .iter()
+        .map(|opt| {
+            let (long, value) = label(opt.long, opt.value);
+            5 + long.len() + value.len()
+        })
+        .max()
+        .unwrap_or(0);
+
+    println!();
+    println!("{SECTION}{BOLD}OPTIONS:{RESET}");
+    let mut group = shown.first().map(|opt| opt.group);
+    for opt in shown {
+        if group != Some(opt.group) {
+            println!();
+            group = Some(opt.group);
+        }
+        let short = opt
+            .short
+            .map(|short| format!("-{}, ", short))
+            .unwrap_or_else(|| "    ".to_string());
+        let (long, value) = label(opt.long, opt.value);
+        let pad = width - (5 + long.len() + value.len());
+        println!(
+            "  {BG_OPTION}{OPTION} {short}{long}{ARG}{value}{RESET}{:pad$} {DESC}{}{RESET}",
+            "", opt.help
+        );
+    }
+    if command.is_none() {
+        println!();
+        println!(
+            "  {DESC}Run {ARG}grim help <command>{RESET}{DESC} to see the options a command accepts.{RESET}"
+        );
+    }
+
+    println!();
+    println!("{SECTION}{BOLD}EXIT CODES:{RESET}");
+    for (code, desc) in [
+        (
+            0,
+            "Every matched process was signalled (or a dry run / help)",
+        ),
+        (1, "No process matched"),
+        (2, "Bad arguments"),
+        (3, "Permission denied, nothing was killed"),
+        (4, "Some processes could not be signalled"),
+        (5, "Interactive mode failed"),
+        (6, "Processes matched but none was killed"),
+        (7, "The config file could not be read or is invalid"),
+        (8, "The daemon could not start"),
+        (9, "The audit log could not be read"),
+        (10, "More processes matched than the safety limit allows"),
+        (11, "The rules file could not be read or is invalid"),
+    ] {
+        println!("  {ARG}{}{RESET}  {DESC}{}{RESET}", code, desc);
+    }
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(limit: usize, yes_many: bool) -> MassLimit {
+        MassLimit {
+            limit,
+            yes_many,
+            confirmed: 0,
+        }
+    }
+
+    #[test]
+    fn mass_limit_needs_the_count_typed_back() {
+        let mut mass = limit(10, false);
+        assert!(!mass.needs_confirmation(10));
+        assert!(mass.needs_confirmation(11));
+
+        assert!(!mass.confirm(&mut "yes\n".as_bytes(), 12));
+        assert!(!mass.confirm(&mut "".as_bytes(), 12));
+        assert!(mass.needs_confirmation(12));
+
+        assert!(mass.confirm(&mut " 12\n".as_bytes(), 12));
+        assert!(!mass.needs_confirmation(12));
+        assert!(mass.needs_confirmation(13));
+    }
+
+    #[test]
+    fn yes_many_and_zero_skip_the_limit() {
+        assert!(!limit(10, true).needs_confirmation(500));
+        assert!(!limit(0, false).needs_confirmation(500));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn other_users_are_told_apart_by_uid() {
+        let owned = |name: &str, uid: &str| ProcessSnapshot {
+            uid: Some(uid.parse().unwrap()),
+            ..ProcessSnapshot::new(Pid::from_u32(1), name)
+        };
+        let processes = [
+            owned("mine", "1000"),
+            owned("init", "0"),
+            owned("web", "33"),
+        ];
+        let me: Uid = "1000".parse().unwrap();
+        let others: Vec<&str> = owned_by_others(&processes, Some(&me))
+            .iter()
+            .map(|process| process.name.as_str())
+            .collect();
+        assert_eq!(others, ["init", "web"]);
+
+        // Running as root, root's own processes need no second confirmation.
+        let root: Uid = "0".parse().unwrap();
+        assert_eq!(owned_by_others(&processes, Some(&root)).len(), 2);
+        assert_eq!(owned_by_others(&processes, None).len(), 3);
+    }
+
+    #[test]
+    fn parses_batch_answers() {
+        for (answer, choice) in [
+            ("a\n", Batch::All),
+            ("ALL", Batch::All),
+            ("n", Batch::None),
+            (" none ", Batch::None),
+            ("s", Batch::Select),
+            ("select", Batch::Select),
+            ("q", Batch::Quit),
+            ("Quit\n", Batch::Quit),
+        ] {
+            assert_eq!(Batch::parse(answer), Some(choice), "{answer:?}");
+        }
+        assert_eq!(Batch::parse(""), None);
+        assert_eq!(Batch::parse("y"), None);
+        assert_eq!(Batch::parse("al"), None);
+    }
+
+    #[test]
+    fn batch_prompt_asks_again_until_it_gets_an_answer() {
+        let mut reporter = Reporter::new(OutputFormat::Json);
+        let mut ask = |input: &str| ask_batch(&mut reporter, &mut input.as_bytes(), "? ");
+        assert_eq!(ask("s\n"), Batch::Select);
+        assert_eq!(ask("maybe\n\nq\n"), Batch::Quit);
+        assert_eq!(ask("maybe\n"), Batch::None);
+        assert_eq!(ask(""), Batch::None);
+    }
+
+    #[test]
+    fn only_yes_confirms() {
+        let mut reporter = Reporter::new(OutputFormat::Json);
+        let mut ask = |input: &str| ask_yes_no(&mut reporter, &mut input.as_bytes(), "? ");
+        assert!(ask("y\n"));
+        assert!(ask(" YES\n"));
+        assert!(!ask("n\n"));
+        assert!(!ask("yep\n"));
+        assert!(!ask(""));
+    }
+}
@@ -0,0 +1,67 @@
+//! Errors only the `grim` binary can hit: terminal, config, rules, daemon and audit
+//! log failures, on top of the library's [`GrimError`].
+
+use std::error::Error;
+
+use grim::GrimError;
+
+/// Why a `grim_command` run did not fully succeed. Each variant has its own exit code.
+#[derive(Debug)]
+pub enum CliError {
+    Grim(GrimError),
+    /// The interactive TUI could not run.
+    Terminal(String),
+    /// The config file could not be read or is invalid.
+    Config(String),
+    /// The rules file could not be read or is invalid.
+    Rules(String),
+    /// `grim daemon` could not start, e.g. because another instance holds the PID file.
+    Daemon(String),
+    /// `grim log` could not read the audit log.
+    AuditLog(String),
+    /// More processes matched than the safety limit allows and the count was not confirmed.
+    TooManyMatches {
+        matched: usize,
+        limit: usize,
+    },
+}
+
+impl CliError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Grim(e) => e.exit_code(),
+            Self::Terminal(_) => 5,
+            Self::Config(_) => 7,
+            Self::Daemon(_) => 8,
+            Self::AuditLog(_) => 9,
+            Self::TooManyMatches { .. } => 10,
+            Self::Rules(_) => 11,
+        }
+    }
+}
+
+impl From<GrimError> for CliError {
+    fn from(e: GrimError) -> Self {
+        Self::Grim(e)
+    }
+}
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Grim(e) => e.fmt(f),
+            Self::Terminal(msg) => write!(f, "interactive mode failed: {}", msg),
+            Self::Config(msg) => write!(f, "bad config file {}", msg),
+            Self::Rules(msg) => write!(f, "bad rules file {}", msg),
+            Self::Daemon(msg) => write!(f, "daemon failed to start: {}", msg),
+            Self::AuditLog(msg) => write!(f, "cannot read audit log {}", msg),
+            Self::TooManyMatches { matched, limit } => write!(
+                f,
+                "{} processes matched, more than the safety limit of {} (pass --yes-many)",
+                matched, limit
+            ),
+        }
+    }
+}
+
+impl Error for CliError {}
@@ -0,0 +1,290 @@
+//! The user's `config.toml`: default flags, protected processes and TUI settings.
+//!
+//! ```toml
+//! protected = ["sshd", "re:^systemd"]
+//!
+//! [defaults]
+//! interval = 5
+//! exact = true
+//!
+//! [safety]
+//! mass_kill_limit = 20
+//!
+//! [tui]
+//! refresh_rate = "1s"
+//!
+//! [tui.colors]
+//! cpu = [20.0, 50.0]
+//! memory = ["512M", "2G"]
+//! ```
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use grim::units::{parse_duration, parse_size};
+use grim::{MatchMode, Matcher};
+use serde::Deserialize;
+use serde::de::{self, Deserializer};
+
+use crate::cli_error::CliError;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Flags applied to every run unless given on the command line.
+    pub defaults: Defaults,
+    /// Processes that are never signalled, from the CLI or the TUI. Plain names
+    /// match exactly; `re:` and `glob:` prefixes work as for targets.
+    #[serde(deserialize_with = "matchers")]
+    pub protected: Vec<Matcher>,
+    /// Where every signal is recorded, instead of `$XDG_STATE_HOME/grim/audit.log`.
+    pub audit_log: Option<PathBuf>,
+    pub safety: Safety,
+    pub tui: TuiConfig,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Defaults {
+    /// Watch mode refresh interval in seconds.
+    pub interval: Option<u64>,
+    pub force: bool,
+    pub exact: bool,
+    pub kill_children: bool,
+}
+
+/// Extra confirmations before grim signals many processes or someone else's.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Safety {
+    /// More matches than this need `--yes-many` or typing the count; 0 disables.
+    pub mass_kill_limit: usize,
+    /// Ask again before signalling processes owned by other users, root
+    /// included unless grim itself runs as root.
+    pub confirm_other_users: bool,
+}
+
+impl Default for Safety {
+    fn default() -> Self {
+        Self {
+            mass_kill_limit: 10,
+            confirm_other_users: true,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TuiConfig {
+    /// How often the process list is re-read.
+    #[serde(deserialize_with = "duration")]
+    pub refresh_rate: Duration,
+    pub colors: Colors,
+}
+
+impl Default for TuiConfig {
+    fn default() -> Self {
+        Self {
+            refresh_rate: Duration::from_millis(200),
+            colors: Colors::default(),
+        }
+    }
+}
+
+/// `[warn, critical]` levels at which the TUI turns a value yellow, then red.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Colors {
+    /// CPU usage in percent.
+    pub cpu: [f32; 2],
+    /// Memory in bytes; also accepts sizes like `"512M"`.
+    #[serde(deserialize_with = "sizes")]
+    pub memory: [u64; 2],
+}
+
+impl Default for Colors {
+    fn default() -> Self {
+        Self {
+            cpu: [20.0, 50.0],
+            memory: [512 * 1024 * 1024, 2 * 1024 * 1024 * 1024],
+        }
+    }
+}
+
+/// `$XDG_STATE_HOME/grim`, falling back to `~/.local/state/grim`: where grim keeps
+/// what it needs to remember between runs.
+pub fn state_dir() -> Option<PathBuf> {
+    env::var_os("XDG_STATE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/state")))
+        .map(|dir| dir.join("grim"))
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/grim/config.toml`, falling back to `~/.config/grim/config.toml`.
+    pub fn default_path() -> Option<PathBuf> {
+        env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+            .map(|dir| dir.join("grim").join("config.toml"))
+    }
+
+    /// Reads `path`, or the default location when `None`. A missing default file
+    /// is not an error; a missing explicit one is.
+    pub fn load(path: Option<&Path>) -> Result<Self, CliError> {
+        let (path, explicit) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => match Self::default_path() {
+                Some(path) => (path, false),
+                None => return Ok(Self::default()),
+            },
+        };
+        match fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text)
+                .map_err(|e| CliError::Config(format!("{}: {}", path.display(), e))),
+            Err(e) if e.kind() == io::ErrorKind::NotFound && !explicit => Ok(Self::default()),
+            Err(e) => Err(CliError::Config(format!("{}: {}", path.display(), e))),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let config: Self = toml::from_str(text).map_err(|e| e.message().to_string())?;
+        let [cpu_warn, cpu_crit] = config.tui.colors.cpu;
+        let [mem_warn, mem_crit] = config.tui.colors.memory;
+        if !(cpu_warn.is_finite() && cpu_crit.is_finite()) || cpu_warn > cpu_crit {
+            return Err("tui.colors.cpu must be [warn, critical] with warn <= critical".into());
+        }
+        if mem_warn > mem_crit {
+            return Err("tui.colors.memory must be [warn, critical] with warn <= critical".into());
+        }
+        if config.defaults.interval == Some(0) {
+            return Err("defaults.interval must be at least 1 second".into());
+        }
+        Ok(config)
+    }
+}
+
+fn duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    let raw = String::deserialize(deserializer)?;
+    parse_duration(&raw)
+        .filter(|duration| !duration.is_zero())
+        .ok_or_else(|| de::Error::custom(format!("invalid duration '{}'", raw)))
+}
+
+fn matchers<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Matcher>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|pattern| Matcher::parse(pattern, MatchMode::Exact, &[], false))
+        .collect::<Result<_, _>>()
+        .map_err(de::Error::custom)
+}
+
+/// A byte count written either as a number or as a size like `"512M"`.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Size {
+    Bytes(u64),
+    Text(String),
+}
+
+impl Size {
+    pub fn bytes(&self) -> Result<u64, String> {
+        match self {
+            Self::Bytes(bytes) => Ok(*bytes),
+            Self::Text(text) => parse_size(text).ok_or_else(|| format!("invalid size '{}'", text)),
+        }
+    }
+}
+
+fn sizes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u64; 2], D::Error> {
+    let [warn, crit] = <[Size; 2]>::deserialize(deserializer)?;
+    Ok([
+        warn.bytes().map_err(de::Error::custom)?,
+        crit.bytes().map_err(de::Error::custom)?,
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_file_gives_defaults() {
+        let config = Config::parse("").unwrap();
+        assert!(config.protected.is_empty());
+        assert!(!config.defaults.force);
+        assert_eq!(config.safety.mass_kill_limit, 10);
+        assert!(config.safety.confirm_other_users);
+        assert_eq!(config.tui.refresh_rate, Duration::from_millis(200));
+        assert_eq!(config.tui.colors.cpu, [20.0, 50.0]);
+        assert_eq!(
+            config.tui.colors.memory,
+            [parse_size("512M").unwrap(), parse_size("2G").unwrap()]
+        );
+    }
+
+    #[test]
+    fn parses_every_section() {
+        let config = Config::parse(
+            r#"
+            protected = ["sshd", "re:^systemd"]
+            audit_log = "/var/log/grim/audit.log"
+
+            [defaults]
+            interval = 5
+            exact = true
+            kill_children = true
+
+            [safety]
+            mass_kill_limit = 0
+            confirm_other_users = false
+
+            [tui]
+            refresh_rate = "1s"
+
+            [tui.colors]
+            cpu = [30, 80.5]
+            memory = ["512M", 4294967296]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.protected.len(), 2);
+        assert_eq!(
+            config.audit_log.as_deref(),
+            Some(Path::new("/var/log/grim/audit.log"))
+        );
+        assert_eq!(config.defaults.interval, Some(5));
+        assert!(config.defaults.exact && config.defaults.kill_children);
+        assert!(!config.defaults.force);
+        assert_eq!(config.safety.mass_kill_limit, 0);
+        assert!(!config.safety.confirm_other_users);
+        assert_eq!(config.tui.refresh_rate, Duration::from_secs(1));
+        assert_eq!(config.tui.colors.cpu, [30.0, 80.5]);
+        assert_eq!(config.tui.colors.memory, [512 * 1024 * 1024, 4294967296]);
+    }
+
+    #[test]
+    fn rejects_bad_values() {
+        assert!(Config::parse("[defaults]\nforse = true").is_err());
+        assert!(Config::parse("[defaults]\ninterval = 0").is_err());
+        assert!(Config::parse("[safety]\nmass_kill_limit = -1").is_err());
+        assert!(Config::parse("[tui]\nrefresh_rate = \"soon\"").is_err());
+        assert!(Config::parse("[tui.colors]\ncpu = [90, 10]").is_err());
+        assert!(Config::parse("protected = [\"re:(\"]").is_err());
+        assert!(Config::parse("[tui.colors]\nmemory = [\"1G\", \"lots\"]").is_err());
+    }
+
+    #[test]
+    fn missing_explicit_file_is_an_error() {
+        let missing = Path::new("/nonexistent/grim/config.toml");
+        assert!(matches!(
+            Config::load(Some(missing)),
+            Err(CliError::Config(_))
+        ));
+    }
+}
@@ -0,0 +1,190 @@
+//! `grim daemon`: watch mode as a long-lived service, e.g. under systemd.
+//!
+//! SIGTERM and SIGINT ask the watch loop to stop after the current check; SIGHUP
+//! asks it to re-read the config and rules files.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::UNIX_EPOCH;
+
+use sysinfo::{Pid, PidExt, ProcessExt, System, SystemExt};
+
+use crate::cli_error::CliError;
+
+static SHUTDOWN: AtomicBool = AtomicBool::new(false);
+static RELOAD: AtomicBool = AtomicBool::new(false);
+
+/// A running daemon. Dropping it removes the PID file.
+pub struct Daemon {
+    pid_file: Option<PathBuf>,
+    /// Where the watch loop writes its (timestamped) output instead of stdout.
+    pub log_file: Option<PathBuf>,
+}
+
+impl Daemon {
+    /// Installs the signal handlers and claims the PID file. Refuses to start when
+    /// the PID file names a daemon that is still running.
+    pub fn start(pid_file: Option<PathBuf>, log_file: Option<PathBuf>) -> Result<Self, CliError> {
+        if let Some(path) = &pid_file {
+            claim_pid_file(path)?;
+        }
+        install_handlers().map_err(|e| CliError::Daemon(e.to_string()))?;
+        Ok(Self { pid_file, log_file })
+    }
+}
+
+/// Writes our PID to `path`, unless it names another daemon that is still running.
+fn claim_pid_file(path: &Path) -> Result<(), CliError> {
+    if let Some(pid) = running_daemon(path) {
+        return Err(CliError::Daemon(format!(
+            "already running as PID {} (see {})",
+            pid,
+            path.display()
+        )));
+    }
+    fs::write(path, format!("{}\n", std::process::id()))
+        .map_err(|e| CliError::Daemon(format!("{}: {}", path.display(), e)))
+}
+
+/// The PID recorded in `path`, if that process is still a grim daemon. A PID the
+/// kernel has since handed to another program, or to a process started after the
+/// file was written, is stale.
+fn running_daemon(path: &Path) -> Option<u32> {
+    let pid = fs::read_to_string(path).ok()?.trim().parse::<u32>().ok()?;
+    let written = fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()?;
+    let me = sysinfo::get_current_pid().ok()?;
+    let mut sys = System::new();
+    sys.refresh_process(me);
+    sys.refresh_process(Pid::from_u32(pid));
+    let proc = sys.process(Pid::from_u32(pid))?;
+    // Start times are whole seconds, so allow for the one the file was written in.
+    let same_program = sys.process(me).is_some_and(|me| me.name() == proc.name());
+    (same_program && proc.start_time() <= written.as_secs() + 1).then_some(pid)
+}
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        if let Some(path) = &self.pid_file {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Whether SIGTERM or SIGINT arrived.
+pub fn shutdown_requested() -> bool {
+    SHUTDOWN.load(Ordering::SeqCst)
+}
+
+/// Whether SIGHUP arrived since the last [`take_reload`].
+pub fn reload_requested() -> bool {
+    RELOAD.load(Ordering::SeqCst)
+}
+
+/// Clears a pending reload, returning whether there was one.
+pub fn take_reload() -> bool {
+    RELOAD.swap(false, Ordering::SeqCst)
+}
+
+#[cfg(unix)]
+extern "C" fn on_signal(signal: libc::c_int) {
+    // Only async-signal-safe work here: flip a flag for the watch loop.
+    if signal == libc::SIGHUP {
+        RELOAD.store(true, Ordering::SeqCst);
+    } else {
+        SHUTDOWN.store(true, Ordering::SeqCst);
+    }
+}
+
+#[cfg(unix)]
+fn install_handlers() -> io::Result<()> {
+    let handler = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    for signal in [libc::SIGTERM, libc::SIGINT, libc::SIGHUP] {
+        // SAFETY: `on_signal` only stores to atomics, which is async-signal-safe.
+        if unsafe { libc::signal(signal, handler) } == libc::SIG_ERR {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn install_handlers() -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn pid_file(name: &str) -> PathBuf {
+        env::temp_dir().join(format!(
+            "grim-daemon-test-{}-{}.pid",
+            std::process::id(),
+            name
+        ))
+    }
+
+    #[test]
+    fn refuses_while_the_daemon_runs() {
+        let path = pid_file("live");
+        fs::write(&path, format!("{}\n", std::process::id())).unwrap();
+        let result = claim_pid_file(&path);
+        let _ = fs::remove_file(&path);
+        assert!(matches!(result, Err(CliError::Daemon(_))));
+    }
+
+    #[test]
+    fn replaces_a_stale_pid_file() {
+        let path = pid_file("stale");
+        fs::write(&path, "999999999\n").unwrap();
+        claim_pid_file(&path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap().trim(),
+            std::process::id().to_string()
+        );
+        let _ = fs::remove_file(&path);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn ignores_a_reused_pid() {
+        let mut other = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let path = pid_file("reused");
+        fs::write(&path, format!("{}\n", other.id())).unwrap();
+        let result = claim_pid_file(&path);
+        let _ = fs::remove_file(&path);
+        other.kill().unwrap();
+        other.wait().unwrap();
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn drop_removes_the_pid_file() {
+        let path = pid_file("drop");
+        fs::write(&path, "1\n").unwrap();
+        drop(Daemon {
+            pid_file: Some(path.clone()),
+            log_file: None,
+        });
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn take_reload_clears_the_request() {
+        RELOAD.store(true, Ordering::SeqCst);
+        assert!(reload_requested());
+        assert!(take_reload());
+        assert!(!reload_requested());
+        assert!(!take_reload());
+    }
+}
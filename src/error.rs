@@ -0,0 +1,51 @@
+//! Why finding and signalling processes did not fully succeed.
+
+use std::error::Error;
+
+/// Why a run did not fully succeed. Each variant has its own exit code.
+#[derive(Debug)]
+pub enum GrimError {
+    /// Invalid or missing command-line arguments.
+    BadArguments(String),
+    /// No process matched any target.
+    NoMatches,
+    /// Nothing was killed and every failed signal was rejected with EPERM.
+    PermissionDenied { denied: usize },
+    /// Some signals were delivered, others failed.
+    PartialFailure { killed: usize, failed: usize },
+    /// Processes matched but none was signalled: every prompt was declined or the
+    /// matches exited first.
+    NothingKilled { matched: usize },
+}
+
+impl GrimError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::NoMatches => 1,
+            Self::BadArguments(_) => 2,
+            Self::PermissionDenied { .. } => 3,
+            Self::PartialFailure { .. } => 4,
+            Self::NothingKilled { .. } => 6,
+        }
+    }
+}
+
+impl std::fmt::Display for GrimError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BadArguments(msg) => write!(f, "{} (see --help)", msg),
+            Self::NoMatches => write!(f, "no matching processes"),
+            Self::PermissionDenied { denied } => {
+                write!(f, "permission denied for {} process(es)", denied)
+            }
+            Self::PartialFailure { killed, failed } => {
+                write!(f, "{} process(es) signalled, {} failed", killed, failed)
+            }
+            Self::NothingKilled { matched } => {
+                write!(f, "{} process(es) matched, none was killed", matched)
+            }
+        }
+    }
+}
+
+impl Error for GrimError {}
@@ -0,0 +1,132 @@
+//! Filters that narrow matches down, and the processes grim never touches.
+
+use std::time::Duration;
+
+use sysinfo::{Pid, PidExt, Process, ProcessExt, System, SystemExt, Uid, UserExt};
+
+use crate::matcher::Matcher;
+use crate::process::process_age;
+
+/// Conditions every matched process must also satisfy.
+#[derive(Default)]
+pub struct Filters {
+    pub user: Option<Uid>,
+    /// CPU usage in percent of one core.
+    pub cpu_above: Option<f32>,
+    /// Resident memory in bytes.
+    pub mem_above: Option<u64>,
+    pub older_than: Option<Duration>,
+    pub younger_than: Option<Duration>,
+}
+
+impl Filters {
+    /// Whether any filter is set, i.e. `--all` will not select every process.
+    pub fn is_restrictive(&self) -> bool {
+        self.user.is_some()
+            || self.has_thresholds()
+            || self.older_than.is_some()
+            || self.younger_than.is_some()
+    }
+
+    pub fn has_thresholds(&self) -> bool {
+        self.cpu_above.is_some() || self.mem_above.is_some()
+    }
+
+    /// Whether `proc` is over every configured resource threshold.
+    pub fn over_threshold(&self, proc: &Process) -> bool {
+        self.cpu_above.is_none_or(|limit| proc.cpu_usage() > limit)
+            && self.mem_above.is_none_or(|limit| proc.memory() > limit)
+    }
+
+    pub fn allows(&self, proc: &Process) -> bool {
+        if let Some(uid) = &self.user
+            && proc.user_id() != Some(uid)
+        {
+            return false;
+        }
+        let age = process_age(proc);
+        if self.older_than.is_some_and(|min| age < min)
+            || self.younger_than.is_some_and(|max| age >= max)
+        {
+            return false;
+        }
+        self.over_threshold(proc)
+    }
+}
+
+/// Processes grim refuses to signal no matter how they were matched.
+#[derive(Clone, Debug)]
+pub struct Protection {
+    /// grim itself, every ancestor up to init (so the launching shell and terminal), and PID 1.
+    pids: Vec<Pid>,
+    /// `--exclude` patterns.
+    excludes: Vec<Matcher>,
+}
+
+impl Protection {
+    /// Ancestors missing from `sys` (say, one refreshed for a few PIDs only) are
+    /// looked up one by one, so the launching shell and terminal stay protected.
+    pub fn new(sys: &System, excludes: Vec<Matcher>) -> Self {
+        let mut pids = vec![Pid::from_u32(1)];
+        let mut lookup = System::new();
+        let mut current = sysinfo::get_current_pid().ok();
+        while let Some(pid) = current {
+            if pids.contains(&pid) {
+                break;
+            }
+            pids.push(pid);
+            current = match sys.process(pid) {
+                Some(proc) => proc.parent(),
+                None => {
+                    lookup.refresh_process(pid);
+                    lookup.process(pid).and_then(|proc| proc.parent())
+                }
+            };
+        }
+        Self { pids, excludes }
+    }
+
+    pub fn protects(&self, proc: &Process) -> bool {
+        self.pids.contains(&proc.pid()) || self.excludes.iter().any(|m| m.is_match(proc))
+    }
+
+    /// Drops protected processes from a [`process_tree`](crate::process::process_tree) listing.
+    pub fn prune(
+        &self,
+        sys: &System,
+        tree: Vec<(Pid, String, usize)>,
+    ) -> Vec<(Pid, String, usize)> {
+        tree.into_iter()
+            .filter(|(pid, _, _)| sys.process(*pid).is_none_or(|proc| !self.protects(proc)))
+            .collect()
+    }
+}
+
+/// Resolves a `--user` argument given as a user name or a numeric uid.
+pub fn resolve_user(sys: &System, user: &str) -> Result<Uid, String> {
+    if let Some(found) = sys
+        .users()
+        .iter()
+        .find(|candidate| candidate.name() == user)
+    {
+        return Ok(found.id().clone());
+    }
+    user.parse::<Uid>()
+        .map_err(|_| format!("Unknown user: {}", user))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn protects_ancestors_missing_from_sys() {
+        let protection = Protection::new(&System::new(), vec![]);
+        let mut sys = System::new();
+        let me = sysinfo::get_current_pid().unwrap();
+        sys.refresh_process(me);
+        let parent = sys.process(me).and_then(|proc| proc.parent()).unwrap();
+        assert!(protection.pids.contains(&me));
+        assert!(protection.pids.contains(&parent));
+    }
+}
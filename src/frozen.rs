@@ -0,0 +1,114 @@
+//! Processes grim stopped with SIGSTOP and has not resumed, remembered in
+//! `$XDG_STATE_HOME/grim/frozen.json` so any later run (or the TUI) can list them.
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use grim::ProcessSnapshot;
+use grim::units::rfc3339;
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, PidExt, ProcessExt, ProcessStatus, System, SystemExt};
+
+use crate::config::state_dir;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FrozenProcess {
+    pub pid: u32,
+    /// Tells the frozen process apart from a later one that reuses its PID.
+    pub start_time: u64,
+    pub name: String,
+    /// When grim froze it.
+    pub since: String,
+}
+
+#[derive(Default)]
+pub struct FrozenList {
+    path: Option<PathBuf>,
+    pub processes: Vec<FrozenProcess>,
+}
+
+impl FrozenList {
+    /// Reads the list, treating a missing or unreadable file as empty.
+    pub fn load() -> Self {
+        let path = state_dir().map(|dir| dir.join("frozen.json"));
+        let processes = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        Self { path, processes }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(&self.processes)?)
+    }
+
+    pub fn contains(&self, pid: Pid) -> bool {
+        self.processes
+            .iter()
+            .any(|frozen| frozen.pid == pid.as_u32())
+    }
+
+    pub fn add(&mut self, process: &ProcessSnapshot) {
+        self.remove(process.pid);
+        self.processes.push(FrozenProcess {
+            pid: process.pid.as_u32(),
+            start_time: process.start_time,
+            name: process.name.clone(),
+            since: rfc3339(SystemTime::now()),
+        });
+    }
+
+    pub fn remove(&mut self, pid: Pid) {
+        self.processes.retain(|frozen| frozen.pid != pid.as_u32());
+    }
+
+    /// Forgets processes that exited, were resumed by something else, or whose
+    /// PID now belongs to a different process. Returns whether anything changed.
+    pub fn prune(&mut self, sys: &System) -> bool {
+        let before = self.processes.len();
+        self.processes.retain(|frozen| {
+            sys.process(Pid::from_u32(frozen.pid)).is_some_and(|proc| {
+                proc.start_time() == frozen.start_time && proc.status() == ProcessStatus::Stop
+            })
+        });
+        self.processes.len() != before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(pid: u32) -> ProcessSnapshot {
+        ProcessSnapshot::new(Pid::from_u32(pid), "make")
+    }
+
+    #[test]
+    fn tracks_each_pid_once() {
+        let mut frozen = FrozenList::default();
+        frozen.add(&snapshot(10));
+        frozen.add(&snapshot(10));
+        frozen.add(&snapshot(11));
+        assert_eq!(frozen.processes.len(), 2);
+        assert!(frozen.contains(Pid::from_u32(10)));
+        frozen.remove(Pid::from_u32(10));
+        assert!(!frozen.contains(Pid::from_u32(10)));
+    }
+
+    #[test]
+    fn prune_forgets_processes_that_are_gone() {
+        let mut frozen = FrozenList::default();
+        frozen.add(&snapshot(999_999));
+        assert!(frozen.prune(&System::new()));
+        assert!(frozen.processes.is_empty());
+    }
+}
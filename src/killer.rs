@@ -0,0 +1,472 @@
+//! Signal delivery with optional TERM→KILL escalation.
+
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use sysinfo::{Pid, ProcessExt, ProcessRefreshKind, ProcessStatus, Signal, System, SystemExt};
+
+use crate::filter::Protection;
+use crate::process::{ProcessSnapshot, process_gone};
+use crate::signal::ProcessHandle;
+
+/// How often a process is re-checked while waiting out a grace period.
+const GRACE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// What happened to one process handed to [`Killer::kill`].
+#[derive(Debug)]
+pub enum KillOutcome {
+    /// The signal was delivered (no grace period or wait configured).
+    Signalled,
+    /// The process exited within the grace period or the wait.
+    Exited,
+    /// The grace period ran out and the process was sent SIGKILL (and, with a
+    /// wait, was then seen to exit).
+    ForceKilled,
+    /// The process died but its parent has not reaped it yet.
+    Zombie,
+    /// The process is protected (grim itself, its ancestors, PID 1 or an exclude)
+    /// and was left alone.
+    Protected,
+    /// The process had already exited (ESRCH) when grim came to signal it.
+    AlreadyGone,
+    /// The PID now belongs to a different process than the one that matched, so
+    /// nothing was signalled.
+    Replaced,
+    /// The signal was delivered but the process was still running after the wait.
+    StillAlive(Duration),
+    /// The signal could not be delivered, e.g. EPERM.
+    Failed(io::Error),
+}
+
+impl KillOutcome {
+    pub fn is_success(&self) -> bool {
+        matches!(
+            self,
+            Self::Signalled | Self::Exited | Self::ForceKilled | Self::Zombie
+        )
+    }
+
+    /// Why the process was not killed, or `None` if it was (or was protected).
+    pub fn failure(&self) -> Option<String> {
+        match self {
+            Self::AlreadyGone => Some("already gone (ESRCH)".to_string()),
+            Self::Replaced => Some("PID reused by a different process".to_string()),
+            Self::StillAlive(wait) => Some(format!("still alive after {} ms", wait.as_millis())),
+            Self::Failed(e) if is_eperm(e) => Some("permission denied (EPERM)".to_string()),
+            Self::Failed(e) => Some(e.to_string()),
+            _ => None,
+        }
+    }
+
+    /// The snake_case name used in JSON output and the audit log.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Signalled => "signalled",
+            Self::Exited => "exited",
+            Self::ForceKilled => "force_killed",
+            Self::Zombie => "zombie",
+            Self::Protected => "protected",
+            Self::AlreadyGone => "already_gone",
+            Self::Replaced => "pid_reused",
+            Self::StillAlive(_) => "still_alive",
+            Self::Failed(_) => "failed",
+        }
+    }
+}
+
+/// Sends one signal to a group of processes, optionally escalating to SIGKILL
+/// for whatever is still running after a grace period.
+///
+/// Protected processes are never signalled. Without [`Killer::with_protection`]
+/// that means grim itself, its ancestors and PID 1.
+#[derive(Clone, Debug)]
+pub struct Killer {
+    signal: Signal,
+    grace: Option<Duration>,
+    wait: Option<Duration>,
+    protection: Option<Protection>,
+}
+
+impl Killer {
+    pub fn new(signal: Signal) -> Self {
+        Self {
+            signal,
+            grace: None,
+            wait: None,
+            protection: None,
+        }
+    }
+
+    /// Waits up to `grace` for the processes to exit before sending SIGKILL.
+    pub fn with_grace(mut self, grace: Option<Duration>) -> Self {
+        self.grace = grace;
+        self
+    }
+
+    /// Checks for up to `wait` that each signalled process is really gone,
+    /// reporting [`KillOutcome::StillAlive`] for those that are not.
+    pub fn with_wait(mut self, wait: Option<Duration>) -> Self {
+        self.wait = wait;
+        self
+    }
+
+    /// Replaces the default protection, e.g. to add `--exclude` patterns.
+    pub fn with_protection(mut self, protection: Protection) -> Self {
+        self.protection = Some(protection);
+        self
+    }
+
+    pub fn signal(&self) -> Signal {
+        self.signal
+    }
+
+    pub fn grace(&self) -> Option<Duration> {
+        self.grace
+    }
+
+    pub fn wait(&self) -> Option<Duration> {
+        self.wait
+    }
+
+    /// Signals every pid in the given order (pass trees leaves first) and waits out
+    /// the grace period and the wait. Returns one outcome per pid, in the same order.
+    /// Each pid must still be the process `sys` last saw under it.
+    pub fn kill(&self, sys: &mut System, pids: &[Pid]) -> Vec<(Pid, KillOutcome)> {
+        let mut outcomes = vec![];
+        let mut group = vec![];
+        for pid in pids {
+            match sys.process(*pid) {
+                Some(proc) => group.push(ProcessSnapshot::capture(sys, proc)),
+                None => outcomes.push((*pid, KillOutcome::AlreadyGone)),
+            }
+        }
+        outcomes.extend(self.kill_snapshots(sys, &group));
+        outcomes.sort_by_key(|(pid, _)| pids.iter().position(|candidate| candidate == pid));
+        outcomes
+    }
+
+    /// Like [`Killer::kill`], for processes matched earlier, e.g. before a prompt.
+    /// A PID that now belongs to a different process gets [`KillOutcome::Replaced`].
+    pub fn kill_snapshots(
+        &self,
+        sys: &mut System,
+        group: &[ProcessSnapshot],
+    ) -> Vec<(Pid, KillOutcome)> {
+        let (mut outcomes, mut escalation) = self.start(sys, group);
+        loop {
+            outcomes.extend(escalation.poll(sys));
+            if escalation.is_empty() {
+                break;
+            }
+            thread::sleep(GRACE_POLL_INTERVAL);
+        }
+        outcomes.sort_by_key(|(pid, _)| group.iter().position(|expected| expected.pid == *pid));
+        outcomes
+    }
+
+    /// Sends the first signal without waiting. Processes still inside their grace
+    /// period or wait are returned in the [`Escalation`] instead of the outcome list, for the
+    /// caller to [`poll`](Escalation::poll) whenever it suits (the TUI does so every frame).
+    ///
+    /// Each process is re-read and compared with its snapshot right before it is
+    /// signalled. On Linux the signal then goes through a pidfd opened before that
+    /// check, so it cannot reach a process that took over the PID in between.
+    pub fn start(
+        &self,
+        sys: &mut System,
+        group: &[ProcessSnapshot],
+    ) -> (Vec<(Pid, KillOutcome)>, Escalation) {
+        let protection = match &self.protection {
+            Some(protection) => protection.clone(),
+            None => Protection::new(sys, vec![]),
+        };
+
+        let mut outcomes = vec![];
+        let mut escalation = Escalation::default();
+        for expected in group {
+            let pid = expected.pid;
+            let Ok(handle) = ProcessHandle::open(pid) else {
+                outcomes.push((pid, KillOutcome::AlreadyGone));
+                continue;
+            };
+            // Skip the CPU refresh: only the identity matters here.
+            let proc = match sys
+                .refresh_process_specifics(pid, ProcessRefreshKind::new())
+                .then(|| sys.process(pid))
+                .flatten()
+            {
+                Some(proc) if !expected.is_same_process(proc) => {
+                    outcomes.push((pid, KillOutcome::Replaced));
+                    continue;
+                }
+                Some(proc) if protection.protects(proc) => {
+                    outcomes.push((pid, KillOutcome::Protected));
+                    continue;
+                }
+                Some(proc) => proc,
+                None => {
+                    outcomes.push((pid, KillOutcome::AlreadyGone));
+                    continue;
+                }
+            };
+            let result = handle.signal(proc, self.signal);
+            let stage = match (self.grace, self.wait) {
+                (Some(grace), wait) => Some((grace, Stage::Grace { wait })),
+                (None, Some(wait)) => Some((
+                    wait,
+                    Stage::Verify {
+                        wait,
+                        force_killed: false,
+                    },
+                )),
+                (None, None) => None,
+            };
+            match (result, stage) {
+                (Ok(()), Some((limit, stage))) => escalation.pending.push(Pending {
+                    handle,
+                    start_time: expected.start_time,
+                    deadline: Instant::now() + limit,
+                    stage,
+                }),
+                (Ok(()), None) => outcomes.push((pid, KillOutcome::Signalled)),
+                (Err(e), _) if is_esrch(&e) => outcomes.push((pid, KillOutcome::AlreadyGone)),
+                (Err(e), _) => outcomes.push((pid, KillOutcome::Failed(e))),
+            }
+        }
+        (outcomes, escalation)
+    }
+}
+
+/// Where a signalled process is in its grace period or wait.
+#[derive(Debug)]
+enum Stage {
+    /// Sent the first signal; SIGKILL follows at the deadline.
+    Grace { wait: Option<Duration> },
+    /// Checking that the process exits before the deadline.
+    Verify { wait: Duration, force_killed: bool },
+}
+
+#[derive(Debug)]
+struct Pending {
+    handle: ProcessHandle,
+    /// Tells the signalled process apart from a later one with the same PID.
+    start_time: u64,
+    deadline: Instant,
+    stage: Stage,
+}
+
+/// Processes that got their first signal and are waiting out a grace period
+/// or being checked for having exited.
+#[derive(Debug, Default)]
+pub struct Escalation {
+    pending: Vec<Pending>,
+}
+
+impl Escalation {
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Adds the processes from another [`Killer::start`] call.
+    pub fn merge(&mut self, other: Escalation) {
+        self.pending.extend(other.pending);
+    }
+
+    /// Checks every pending process without blocking. Returns outcomes for those
+    /// that exited, sends SIGKILL to those whose grace period ran out and gives up
+    /// on those still running after their wait.
+    pub fn poll(&mut self, sys: &mut System) -> Vec<(Pid, KillOutcome)> {
+        let now = Instant::now();
+        let mut outcomes = vec![];
+        self.pending.retain_mut(|pending| {
+            let pid = pending.handle.pid();
+            let force_killed = matches!(
+                pending.stage,
+                Stage::Verify {
+                    force_killed: true,
+                    ..
+                }
+            );
+            let replaced = |sys: &System| {
+                sys.process(pid)
+                    .is_some_and(|proc| proc.start_time() != pending.start_time)
+            };
+            if process_gone(sys, pid) || replaced(sys) {
+                let outcome = if !replaced(sys)
+                    && sys
+                        .process(pid)
+                        .is_some_and(|proc| proc.status() == ProcessStatus::Zombie)
+                {
+                    KillOutcome::Zombie
+                } else if force_killed {
+                    KillOutcome::ForceKilled
+                } else {
+                    KillOutcome::Exited
+                };
+                outcomes.push((pid, outcome));
+                return false;
+            }
+            if now < pending.deadline {
+                return true;
+            }
+            let outcome = match pending.stage {
+                Stage::Grace { wait } => {
+                    match sys
+                        .process(pid)
+                        .map(|proc| pending.handle.signal(proc, Signal::Kill))
+                    {
+                        Some(Ok(())) => match wait {
+                            Some(wait) => {
+                                pending.deadline = now + wait;
+                                pending.stage = Stage::Verify {
+                                    wait,
+                                    force_killed: true,
+                                };
+                                return true;
+                            }
+                            None => KillOutcome::ForceKilled,
+                        },
+                        Some(Err(e)) if is_esrch(&e) => KillOutcome::Exited,
+                        Some(Err(e)) => KillOutcome::Failed(e),
+                        None => KillOutcome::Exited,
+                    }
+                }
+                Stage::Verify { wait, .. } => KillOutcome::StillAlive(wait),
+            };
+            outcomes.push((pid, outcome));
+            false
+        });
+        outcomes
+    }
+}
+
+#[cfg(unix)]
+fn is_esrch(e: &io::Error) -> bool {
+    e.raw_os_error() == Some(libc::ESRCH)
+}
+
+#[cfg(not(unix))]
+fn is_esrch(_e: &io::Error) -> bool {
+    false
+}
+
+#[cfg(unix)]
+fn is_eperm(e: &io::Error) -> bool {
+    e.raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+fn is_eperm(e: &io::Error) -> bool {
+    e.kind() == io::ErrorKind::PermissionDenied
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failures_say_why() {
+        assert_eq!(KillOutcome::Zombie.failure(), None);
+        assert!(KillOutcome::Zombie.is_success());
+        assert_eq!(
+            KillOutcome::StillAlive(Duration::from_millis(1500)).failure(),
+            Some("still alive after 1500 ms".to_string())
+        );
+        assert!(!KillOutcome::AlreadyGone.is_success());
+        assert_eq!(KillOutcome::AlreadyGone.label(), "already_gone");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reused_pids_are_left_alone() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let pid = Pid::from(child.id() as usize);
+        let mut sys = System::new();
+        sys.refresh_process(pid);
+        let mut stale = ProcessSnapshot::capture(&sys, sys.process(pid).unwrap());
+        stale.start_time -= 1;
+
+        let killer = Killer::new(Signal::Kill);
+        let outcomes = killer.kill_snapshots(&mut sys, &[stale]);
+        assert!(matches!(outcomes[0].1, KillOutcome::Replaced));
+        assert!(child.try_wait().unwrap().is_none());
+
+        let outcomes = killer.kill(&mut sys, &[pid]);
+        assert!(matches!(outcomes[0].1, KillOutcome::Signalled));
+        assert!(!child.wait().unwrap().success());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn grace_periods_run_side_by_side() {
+        let mut children: Vec<_> = (0..3)
+            .map(|_| {
+                std::process::Command::new("sh")
+                    .args(["-c", "trap '' TERM; exec sleep 30"])
+                    .spawn()
+                    .unwrap()
+            })
+            .collect();
+        // Wait for the exec, so SIGTERM finds the trap in place.
+        let deadline = Instant::now() + Duration::from_secs(5);
+        for child in &children {
+            let comm = format!("/proc/{}/comm", child.id());
+            while std::fs::read_to_string(&comm).unwrap().trim() != "sleep" {
+                assert!(Instant::now() < deadline);
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+        let mut sys = System::new();
+        let mut group = vec![];
+        for child in &children {
+            let pid = Pid::from(child.id() as usize);
+            sys.refresh_process(pid);
+            group.push(ProcessSnapshot::capture(&sys, sys.process(pid).unwrap()));
+        }
+
+        let grace = Duration::from_secs(1);
+        let killer = Killer::new(Signal::Term).with_grace(Some(grace));
+        let started = Instant::now();
+        let mut escalation = Escalation::default();
+        for member in &group {
+            let (outcomes, pending) = killer.start(&mut sys, std::slice::from_ref(member));
+            assert!(outcomes.is_empty());
+            escalation.merge(pending);
+        }
+        assert_eq!(escalation.len(), 3);
+        let mut outcomes = vec![];
+        while !escalation.is_empty() {
+            outcomes.extend(escalation.poll(&mut sys));
+            thread::sleep(GRACE_POLL_INTERVAL);
+        }
+        // One grace period for all three, not one after another.
+        assert!(started.elapsed() < grace * 2);
+        assert_eq!(outcomes.len(), 3);
+        assert!(outcomes.iter().all(|(_, outcome)| outcome.is_success()));
+        for child in &mut children {
+            assert!(!child.wait().unwrap().success());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn eperm_and_esrch_are_recognised() {
+        let eperm = KillOutcome::Failed(io::Error::from_raw_os_error(libc::EPERM));
+        assert_eq!(
+            eperm.failure().as_deref(),
+            Some("permission denied (EPERM)")
+        );
+
+        let mut sys = System::new();
+        let outcomes = Killer::new(Signal::Term).kill(&mut sys, &[Pid::from(999_999_999)]);
+        assert!(matches!(outcomes[0].1, KillOutcome::AlreadyGone));
+    }
+}
@@ -0,0 +1,22 @@
+//! The process matching and signalling behind the `grim` CLI and TUI.
+//!
+//! Build with `default-features = false` to use it without the terminal front-ends.
+
+pub mod error;
+pub mod filter;
+pub mod killer;
+pub mod matcher;
+pub mod port;
+pub mod priority;
+pub mod process;
+pub mod signal;
+pub mod units;
+
+pub use error::GrimError;
+pub use filter::{Filters, Protection, resolve_user};
+pub use killer::{Escalation, KillOutcome, Killer};
+pub use matcher::{Match, MatchField, MatchMode, Matcher, Target, collect_matches};
+pub use port::{PortSpec, Protocol};
+pub use priority::{get_nice, parse_nice, scheduling_policy, set_nice};
+pub use process::{ProcessSnapshot, process_tree, snapshot_all};
+pub use signal::{parse_signal, send_signal, signal_name};
@@ -0,0 +1,330 @@
+//! Target matching: PIDs, name patterns and the deduplicated match set.
+
+use std::collections::HashMap;
+
+use regex::{Regex, RegexBuilder};
+use sysinfo::{Pid, PidExt, Process, ProcessExt, System, SystemExt};
+
+use crate::filter::Protection;
+use crate::port::PortSpec;
+use crate::process::ProcessSnapshot;
+
+/// A single command-line target.
+pub enum Target {
+    Pid(Pid),
+    Pattern(Matcher),
+    /// Processes with a socket on a local port, written `:3000` or `:3000/tcp`.
+    Port(PortSpec),
+    /// Every process, narrowed down by [`Filters`](crate::Filters).
+    All,
+}
+
+impl Target {
+    /// Parses a target: a number is a PID, `:port` a port, anything else a [`Matcher`]
+    /// pattern.
+    pub fn parse(
+        target: &str,
+        default_mode: MatchMode,
+        fields: &[MatchField],
+        case_sensitive: bool,
+    ) -> Result<Self, String> {
+        if let Some(port) = target.strip_prefix(':') {
+            return PortSpec::parse(port).map(Self::Port).ok_or_else(|| {
+                format!(
+                    "Invalid port: {} (expected e.g. :3000 or :3000/tcp)",
+                    target
+                )
+            });
+        }
+        match target.parse::<u32>() {
+            Ok(pid) => Ok(Self::Pid(Pid::from_u32(pid))),
+            Err(_) => {
+                Matcher::parse(target, default_mode, fields, case_sensitive).map(Self::Pattern)
+            }
+        }
+    }
+
+    /// Every process in `sys` this target selects, before any filtering.
+    pub fn candidates<'a>(&self, sys: &'a System) -> Vec<&'a Process> {
+        match self {
+            Self::Pid(pid) => sys.process(*pid).into_iter().collect(),
+            Self::Pattern(matcher) => sys
+                .processes()
+                .values()
+                .filter(|proc| matcher.is_match(proc))
+                .collect(),
+            Self::Port(spec) => {
+                let owners = spec.owners();
+                sys.processes()
+                    .values()
+                    .filter(|proc| owners.contains(&proc.pid()))
+                    .collect()
+            }
+            Self::All => sys.processes().values().collect(),
+        }
+    }
+}
+
+/// A process selected by one or more targets.
+#[derive(Clone, Debug)]
+pub struct Match {
+    pub process: ProcessSnapshot,
+    /// Every target that selected this process, in command-line order.
+    pub matched_by: Vec<String>,
+}
+
+/// Runs every labelled target against `sys` and merges the results into one
+/// list with a single entry per PID, in first-match order. Processes covered by
+/// `protection` are always dropped, as are candidates for which `keep` returns false.
+pub fn collect_matches(
+    sys: &System,
+    targets: &[(String, Target)],
+    protection: &Protection,
+    keep: impl Fn(&Process) -> bool,
+) -> Vec<Match> {
+    let mut matches: Vec<Match> = vec![];
+    let mut index: HashMap<Pid, usize> = HashMap::new();
+    for (label, target) in targets {
+        for proc in target
+            .candidates(sys)
+            .into_iter()
+            .filter(|proc| !protection.protects(proc) && keep(proc))
+        {
+            match index.get(&proc.pid()) {
+                Some(idx) => matches[*idx].matched_by.push(label.clone()),
+                None => {
+                    index.insert(proc.pid(), matches.len());
+                    matches.push(Match {
+                        process: ProcessSnapshot::capture(sys, proc),
+                        matched_by: vec![label.clone()],
+                    });
+                }
+            }
+        }
+    }
+    matches
+}
+
+/// How a target pattern is compared against process fields.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MatchMode {
+    /// The field contains the pattern.
+    Substring,
+    /// The field equals the pattern.
+    Exact,
+    /// The field contains a match for the regular expression.
+    Regex,
+    /// The whole field matches the shell-style glob (`*`, `?`, `[...]`).
+    Glob,
+}
+
+/// Process fields a pattern can be matched against.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MatchField {
+    Name,
+    Cmd,
+    Exe,
+}
+
+impl MatchField {
+    pub fn parse(input: &str) -> Option<Self> {
+        match input.trim().to_lowercase().as_str() {
+            "name" => Some(Self::Name),
+            "cmd" | "cmdline" => Some(Self::Cmd),
+            "exe" | "path" => Some(Self::Exe),
+            _ => None,
+        }
+    }
+
+    pub fn value(self, proc: &Process) -> String {
+        match self {
+            Self::Name => proc.name().to_string(),
+            Self::Cmd => proc.cmd().join(" "),
+            Self::Exe => proc.exe().to_string_lossy().into_owned(),
+        }
+    }
+}
+
+/// A compiled name pattern, shared by CLI targets and the TUI filter.
+#[derive(Clone, Debug)]
+pub struct Matcher {
+    mode: MatchMode,
+    fields: Vec<MatchField>,
+    case_sensitive: bool,
+    pattern: String,
+    regex: Option<Regex>,
+}
+
+impl Matcher {
+    /// Builds a matcher from `target`. A `re:`, `glob:` or `exact:` prefix overrides
+    /// `default_mode`. With no `fields`, exact patterns look at the process name and
+    /// everything else at the name and command line.
+    pub fn parse(
+        target: &str,
+        default_mode: MatchMode,
+        fields: &[MatchField],
+        case_sensitive: bool,
+    ) -> Result<Self, String> {
+        let (mode, pattern) = if let Some(rest) = target.strip_prefix("re:") {
+            (MatchMode::Regex, rest)
+        } else if let Some(rest) = target.strip_prefix("glob:") {
+            (MatchMode::Glob, rest)
+        } else if let Some(rest) = target.strip_prefix("exact:") {
+            (MatchMode::Exact, rest)
+        } else {
+            (default_mode, target)
+        };
+
+        let fields = if !fields.is_empty() {
+            fields.to_vec()
+        } else if mode == MatchMode::Exact {
+            vec![MatchField::Name]
+        } else {
+            vec![MatchField::Name, MatchField::Cmd]
+        };
+
+        let regex = match mode {
+            MatchMode::Regex => Some(pattern.to_string()),
+            MatchMode::Glob => Some(glob_to_regex(pattern)),
+            MatchMode::Substring | MatchMode::Exact => None,
+        }
+        .map(|source| {
+            RegexBuilder::new(&source)
+                .case_insensitive(!case_sensitive)
+                .build()
+                .map_err(|e| format!("Invalid pattern '{}': {}", pattern, e))
+        })
+        .transpose()?;
+
+        Ok(Self {
+            mode,
+            fields,
+            case_sensitive,
+            pattern: if case_sensitive {
+                pattern.to_string()
+            } else {
+                pattern.to_lowercase()
+            },
+            regex,
+        })
+    }
+
+    pub fn is_match(&self, proc: &Process) -> bool {
+        self.is_match_with(|field| field.value(proc))
+    }
+
+    /// Matches against field values from somewhere other than a live process,
+    /// such as a recorded snapshot.
+    pub fn is_match_with(&self, value_of: impl Fn(MatchField) -> String) -> bool {
+        self.fields.iter().any(|field| {
+            let value = value_of(*field);
+            if let Some(regex) = &self.regex {
+                return regex.is_match(&value);
+            }
+            let value = if self.case_sensitive {
+                value
+            } else {
+                value.to_lowercase()
+            };
+            match self.mode {
+                MatchMode::Exact => value == self.pattern,
+                _ => value.contains(&self.pattern),
+            }
+        })
+    }
+}
+
+/// Translates a shell-style glob into an anchored regular expression.
+fn glob_to_regex(glob: &str) -> String {
+    let mut out = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => out.push_str(".*"),
+            '?' => out.push('.'),
+            '[' => {
+                out.push('[');
+                if chars.peek() == Some(&'!') {
+                    chars.next();
+                    out.push('^');
+                }
+                // A `]` right after the opening bracket is a literal member, as in POSIX.
+                if chars.peek() == Some(&']') {
+                    chars.next();
+                    out.push_str("\\]");
+                }
+                let mut prev = None;
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                    // Escape what the regex crate would read as nested classes or the
+                    // `&&`, `--` and `~~` set operators; glob classes have neither.
+                    let operator = matches!(c, '&' | '~') || (c == '-' && prev == Some('-'));
+                    if c == '\\' || c == '[' || operator {
+                        out.push('\\');
+                    }
+                    out.push(c);
+                    prev = Some(c);
+                }
+                out.push(']');
+            }
+            c => out.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    out.push('$');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glob(pattern: &str) -> Regex {
+        Regex::new(&glob_to_regex(pattern)).unwrap()
+    }
+
+    #[test]
+    fn colon_targets_are_ports() {
+        let parse = |target| Target::parse(target, MatchMode::Substring, &[], false);
+        assert!(matches!(parse(":3000"), Ok(Target::Port(spec)) if spec.port == 3000));
+        assert!(matches!(parse("3000"), Ok(Target::Pid(_))));
+        assert!(parse(":http").is_err());
+        assert!(parse(":3000/icmp").is_err());
+    }
+
+    #[test]
+    fn glob_wildcards_are_anchored() {
+        assert_eq!(glob_to_regex("node*"), "^node.*$");
+        assert!(glob("chrom?").is_match("chrome"));
+        assert!(!glob("chrom?").is_match("chromium"));
+        assert!(glob("a.b").is_match("a.b"));
+        assert!(!glob("a.b").is_match("axb"));
+    }
+
+    #[test]
+    fn glob_classes() {
+        assert!(glob("py[23]").is_match("py3"));
+        assert!(!glob("py[!23]").is_match("py3"));
+        assert!(glob("py[!23]").is_match("py4"));
+        assert!(glob("[a-c]x").is_match("bx"));
+    }
+
+    #[test]
+    fn glob_leading_bracket_is_literal() {
+        assert!(glob("[]x]").is_match("]"));
+        assert!(glob("[]x]").is_match("x"));
+        assert!(glob("[!]]").is_match("a"));
+        assert!(!glob("[!]]").is_match("]"));
+    }
+
+    #[test]
+    fn glob_class_operators_are_literal() {
+        assert!(glob("[a&&b]").is_match("&"));
+        assert!(glob("[a~~b]").is_match("~"));
+        // `+--` is the range from `+` to `-`, not a set difference.
+        assert!(glob("[+--]").is_match(","));
+        assert!(!glob("[+--]").is_match("a"));
+        assert!(!glob("[a&&b]").is_match("c"));
+    }
+}
@@ -0,0 +1,186 @@
+//! Port targets: the processes holding a TCP or UDP socket on a local port.
+
+use std::collections::HashSet;
+#[cfg(target_os = "linux")]
+use std::fs;
+#[cfg(target_os = "linux")]
+use std::path::Path;
+
+use sysinfo::Pid;
+#[cfg(target_os = "linux")]
+use sysinfo::PidExt;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Protocol {
+    Tcp,
+    Udp,
+}
+
+impl Protocol {
+    pub fn parse(input: &str) -> Option<Self> {
+        match input.trim().to_lowercase().as_str() {
+            "tcp" => Some(Self::Tcp),
+            "udp" => Some(Self::Udp),
+            _ => None,
+        }
+    }
+
+    /// The kernel's socket tables for this protocol, IPv4 then IPv6.
+    #[cfg(target_os = "linux")]
+    fn tables(self) -> [&'static str; 2] {
+        match self {
+            Self::Tcp => ["/proc/net/tcp", "/proc/net/tcp6"],
+            Self::Udp => ["/proc/net/udp", "/proc/net/udp6"],
+        }
+    }
+}
+
+/// A local port, optionally limited to one protocol.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PortSpec {
+    pub port: u16,
+    /// `None` searches both TCP and UDP.
+    pub protocol: Option<Protocol>,
+}
+
+impl PortSpec {
+    /// Parses `3000`, `3000/tcp` or `3000/udp`.
+    pub fn parse(input: &str) -> Option<Self> {
+        let (port, protocol) = match input.split_once('/') {
+            Some((port, protocol)) => (port, Some(Protocol::parse(protocol)?)),
+            None => (input, None),
+        };
+        let port = port.trim().parse::<u16>().ok().filter(|port| *port > 0)?;
+        Some(Self { port, protocol })
+    }
+
+    /// Every process with a listening or connected socket on this port. Sockets of
+    /// other users' processes are only visible to root.
+    #[cfg(target_os = "linux")]
+    pub fn owners(&self) -> HashSet<Pid> {
+        let protocols = match self.protocol {
+            Some(protocol) => vec![protocol],
+            None => vec![Protocol::Tcp, Protocol::Udp],
+        };
+        let inodes: HashSet<u64> = protocols
+            .iter()
+            .flat_map(|protocol| protocol.tables())
+            .filter_map(|table| fs::read_to_string(table).ok())
+            .flat_map(|table| socket_inodes(&table, self.port))
+            .collect();
+        if inodes.is_empty() {
+            return HashSet::new();
+        }
+        let Ok(entries) = fs::read_dir("/proc") else {
+            return HashSet::new();
+        };
+        entries
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+            .filter(|pid| holds_socket(*pid, &inodes))
+            .map(Pid::from_u32)
+            .collect()
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn owners(&self) -> HashSet<Pid> {
+        HashSet::new()
+    }
+}
+
+/// Inodes of the sockets bound to local `port` in a `/proc/net/{tcp,udp}[6]` table.
+pub fn socket_inodes(table: &str, port: u16) -> Vec<u64> {
+    table
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            // `sl local_address rem_address st ... uid timeout inode`, addresses as HEXIP:HEXPORT.
+            let (_, local_port) = fields.get(1)?.rsplit_once(':')?;
+            let inode = fields.get(9)?.parse::<u64>().ok()?;
+            // Sockets in TIME_WAIT belong to no process and have inode 0.
+            (u16::from_str_radix(local_port, 16).ok()? == port && inode != 0).then_some(inode)
+        })
+        .collect()
+}
+
+/// Whether any of `pid`'s file descriptors is one of `inodes`.
+#[cfg(target_os = "linux")]
+fn holds_socket(pid: u32, inodes: &HashSet<u64>) -> bool {
+    let Ok(fds) = fs::read_dir(format!("/proc/{}/fd", pid)) else {
+        return false;
+    };
+    fds.flatten().any(|fd| {
+        fs::read_link(fd.path())
+            .ok()
+            .and_then(|link| socket_inode(&link))
+            .is_some_and(|inode| inodes.contains(&inode))
+    })
+}
+
+/// The inode in a `socket:[12345]` fd link.
+#[cfg(target_os = "linux")]
+fn socket_inode(link: &Path) -> Option<u64> {
+    link.to_str()?
+        .strip_prefix("socket:[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_port_specs() {
+        assert_eq!(
+            PortSpec::parse("3000"),
+            Some(PortSpec {
+                port: 3000,
+                protocol: None
+            })
+        );
+        assert_eq!(
+            PortSpec::parse("53/UDP"),
+            Some(PortSpec {
+                port: 53,
+                protocol: Some(Protocol::Udp)
+            })
+        );
+        assert_eq!(PortSpec::parse("0"), None);
+        assert_eq!(PortSpec::parse("70000"), None);
+        assert_eq!(PortSpec::parse("3000/sctp"), None);
+        assert_eq!(PortSpec::parse("http"), None);
+    }
+
+    #[test]
+    fn reads_socket_tables() {
+        let table = "\
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:0BB8 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 4242 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:0BB8 0100007F:D431 06 00000000:00000000 03:00000F9E 00000000     0        0 0 3 0000000000000000
+   2: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 4343 1 0000000000000000 100 0 0 10 0
+";
+        assert_eq!(socket_inodes(table, 3000), [4242]);
+        assert_eq!(socket_inodes(table, 8080), [4343]);
+        assert!(socket_inodes(table, 22).is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn finds_the_listening_process() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let spec = PortSpec {
+            port: listener.local_addr().unwrap().port(),
+            protocol: Some(Protocol::Tcp),
+        };
+        let me = sysinfo::get_current_pid().unwrap();
+        assert!(spec.owners().contains(&me));
+        let udp = PortSpec {
+            protocol: Some(Protocol::Udp),
+            ..spec
+        };
+        assert!(!udp.owners().contains(&me));
+    }
+}
@@ -0,0 +1,108 @@
+//! Nice values and scheduling policies.
+
+use std::io;
+use std::ops::RangeInclusive;
+
+use sysinfo::Pid;
+#[cfg(unix)]
+use sysinfo::PidExt;
+
+/// Nice values the kernel accepts, from highest priority to lowest.
+pub const NICE_RANGE: RangeInclusive<i32> = -20..=19;
+
+/// Parses a nice value such as `10` or `-5`.
+pub fn parse_nice(input: &str) -> Option<i32> {
+    input
+        .trim()
+        .parse::<i32>()
+        .ok()
+        .filter(|nice| NICE_RANGE.contains(nice))
+}
+
+/// The nice value of `pid`.
+#[cfg(unix)]
+pub fn get_nice(pid: Pid) -> io::Result<i32> {
+    // SAFETY: getpriority(2) and kill(2) have no memory-safety preconditions.
+    let nice = unsafe { libc::getpriority(libc::PRIO_PROCESS as _, pid.as_u32() as libc::id_t) };
+    // -1 is also a valid nice value; only a missing process makes it an error.
+    if nice == -1 && unsafe { libc::kill(pid.as_u32() as libc::pid_t, 0) } != 0 {
+        let e = io::Error::last_os_error();
+        if e.raw_os_error() == Some(libc::ESRCH) {
+            return Err(e);
+        }
+    }
+    Ok(nice)
+}
+
+#[cfg(not(unix))]
+pub fn get_nice(_pid: Pid) -> io::Result<i32> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+/// Sets the nice value of `pid`. Raising priority (lowering the value) usually needs root.
+#[cfg(unix)]
+pub fn set_nice(pid: Pid, nice: i32) -> io::Result<()> {
+    // SAFETY: setpriority(2) has no memory-safety preconditions.
+    if unsafe {
+        libc::setpriority(
+            libc::PRIO_PROCESS as _,
+            pid.as_u32() as libc::id_t,
+            nice.clamp(*NICE_RANGE.start(), *NICE_RANGE.end()),
+        )
+    } == 0
+    {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(unix))]
+pub fn set_nice(_pid: Pid, _nice: i32) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+/// The scheduling policy of `pid`, like `SCHED_OTHER`, where the platform reports one.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn scheduling_policy(pid: Pid) -> Option<&'static str> {
+    // SAFETY: sched_getscheduler(2) has no memory-safety preconditions.
+    Some(
+        match unsafe { libc::sched_getscheduler(pid.as_u32() as libc::pid_t) } {
+            libc::SCHED_OTHER => "SCHED_OTHER",
+            libc::SCHED_FIFO => "SCHED_FIFO",
+            libc::SCHED_RR => "SCHED_RR",
+            libc::SCHED_BATCH => "SCHED_BATCH",
+            libc::SCHED_IDLE => "SCHED_IDLE",
+            // SCHED_DEADLINE, which libc does not name.
+            6 => "SCHED_DEADLINE",
+            _ => return None,
+        },
+    )
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn scheduling_policy(_pid: Pid) -> Option<&'static str> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nice_values() {
+        assert_eq!(parse_nice("10"), Some(10));
+        assert_eq!(parse_nice("-20"), Some(-20));
+        assert_eq!(parse_nice("+5"), Some(5));
+        assert_eq!(parse_nice("20"), None);
+        assert_eq!(parse_nice("high"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reads_own_priority() {
+        let pid = sysinfo::get_current_pid().unwrap();
+        assert!(NICE_RANGE.contains(&get_nice(pid).unwrap()));
+        assert!(get_nice(Pid::from_u32(999_999_999)).is_err());
+    }
+}
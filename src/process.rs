@@ -0,0 +1,150 @@
+//! Process snapshots and parent/child relationships.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use sysinfo::{Pid, Process, ProcessExt, ProcessStatus, System, SystemExt, Uid, UserExt};
+
+/// A point-in-time copy of the process fields grim reports on.
+#[derive(Clone, Debug)]
+pub struct ProcessSnapshot {
+    pub pid: Pid,
+    pub parent: Option<Pid>,
+    pub name: String,
+    pub cmd: String,
+    pub exe: PathBuf,
+    /// Owning user name, or the raw uid when it has no passwd entry.
+    pub user: String,
+    pub uid: Option<Uid>,
+    /// CPU usage in percent of one core.
+    pub cpu: f32,
+    /// Resident memory in bytes.
+    pub memory: u64,
+    pub age: Duration,
+    /// Seconds since the epoch. Together with the PID it tells processes apart.
+    pub start_time: u64,
+    pub status: ProcessStatus,
+}
+
+impl ProcessSnapshot {
+    /// A snapshot with just a PID and a name, every other field empty; for building
+    /// fixtures without a live process.
+    pub fn new(pid: Pid, name: &str) -> Self {
+        Self {
+            pid,
+            parent: None,
+            name: name.to_string(),
+            cmd: String::new(),
+            exe: PathBuf::new(),
+            user: String::new(),
+            uid: None,
+            cpu: 0.0,
+            memory: 0,
+            age: Duration::ZERO,
+            start_time: 0,
+            status: ProcessStatus::Unknown(0),
+        }
+    }
+
+    pub fn capture(sys: &System, proc: &Process) -> Self {
+        Self {
+            pid: proc.pid(),
+            parent: proc.parent(),
+            name: proc.name().to_string(),
+            cmd: proc.cmd().join(" "),
+            exe: proc.exe().to_path_buf(),
+            user: owner_name(sys, proc),
+            uid: proc.user_id().cloned(),
+            cpu: proc.cpu_usage(),
+            memory: proc.memory(),
+            age: process_age(proc),
+            start_time: proc.start_time(),
+            status: proc.status(),
+        }
+    }
+
+    /// Whether `proc` is still the process this snapshot was taken of, and not a
+    /// later one that got the same PID: same start time and, where both are known,
+    /// the same executable.
+    pub fn is_same_process(&self, proc: &Process) -> bool {
+        proc.pid() == self.pid
+            && proc.start_time() == self.start_time
+            && (self.exe.as_os_str().is_empty()
+                || proc.exe().as_os_str().is_empty()
+                || proc.exe() == self.exe)
+    }
+}
+
+/// Snapshots every process `sys` currently knows about.
+pub fn snapshot_all(sys: &System) -> Vec<ProcessSnapshot> {
+    sys.processes()
+        .values()
+        .map(|proc| ProcessSnapshot::capture(sys, proc))
+        .collect()
+}
+
+/// How long `proc` has been running, from its start time where available.
+pub fn process_age(proc: &Process) -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .and_then(|now| now.checked_sub(Duration::from_secs(proc.start_time())))
+        .filter(|_| proc.start_time() > 0)
+        .unwrap_or_else(|| Duration::from_secs(proc.run_time()))
+}
+
+/// Name of the user owning `proc`, falling back to the raw uid.
+pub fn owner_name(sys: &System, proc: &Process) -> String {
+    match proc.user_id() {
+        Some(uid) => sys
+            .get_user_by_id(uid)
+            .map(|user| user.name().to_string())
+            .unwrap_or_else(|| format!("{:?}", uid)),
+        None => "?".to_string(),
+    }
+}
+
+/// Every descendant of `root` in depth-first order, with its depth below `root`
+/// (direct children are at depth 1). Reversing the list gives a leaves-first order.
+pub fn process_tree(sys: &System, root: Pid) -> Vec<(Pid, String, usize)> {
+    let mut children: HashMap<Pid, Vec<Pid>> = HashMap::new();
+    for (pid, proc) in sys.processes() {
+        if let Some(parent) = proc.parent()
+            && parent != *pid
+        {
+            children.entry(parent).or_default().push(*pid);
+        }
+    }
+    for pids in children.values_mut() {
+        pids.sort();
+    }
+
+    let mut tree = vec![];
+    let mut seen = vec![root];
+    let mut stack: Vec<(Pid, usize)> = children
+        .get(&root)
+        .map(|pids| pids.iter().rev().map(|pid| (*pid, 1)).collect())
+        .unwrap_or_default();
+    while let Some((pid, depth)) = stack.pop() {
+        if seen.contains(&pid) {
+            continue;
+        }
+        seen.push(pid);
+        if let Some(proc) = sys.process(pid) {
+            tree.push((pid, proc.name().to_string(), depth));
+        }
+        if let Some(pids) = children.get(&pid) {
+            stack.extend(pids.iter().rev().map(|child| (*child, depth + 1)));
+        }
+    }
+    tree
+}
+
+/// Refreshes `pid` and reports whether it has exited (zombies count as exited).
+pub fn process_gone(sys: &mut System, pid: Pid) -> bool {
+    !sys.refresh_process(pid)
+        || sys
+            .process(pid)
+            .is_none_or(|proc| proc.status() == ProcessStatus::Zombie)
+}
@@ -0,0 +1,422 @@
+//! Watch rules: each one pairs a process selection with what to do about it.
+//!
+//! ```toml
+//! [[rule]]
+//! name = "runaway node"
+//! process = "node"
+//! cpu_above = 90
+//! for = "1m"
+//! signal = "TERM"
+//! grace = "10s"
+//! wait = "5s"
+//! cooldown = "5m"
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use grim::units::{format_duration, parse_duration};
+use grim::{
+    Filters, Killer, Match, MatchField, MatchMode, Protection, Target, collect_matches,
+    parse_signal, resolve_user, signal_name,
+};
+use serde::Deserialize;
+use sysinfo::{Pid, Process, ProcessExt, Signal, System, SystemExt};
+
+use crate::cli_error::CliError;
+use crate::config::Size;
+
+/// One selection plus the action taken on whatever it selects. Running grim with
+/// targets on the command line builds a single unnamed rule.
+pub struct Rule {
+    /// Shown next to each match; empty for the command-line rule.
+    pub name: String,
+    pub targets: Vec<(String, Target)>,
+    pub filters: Filters,
+    /// Thresholds must hold this long across checks before a process matches.
+    pub sustain: Option<Duration>,
+    pub killer: Killer,
+    pub kill_children: bool,
+    /// The rule stops acting after this many kills.
+    pub max: Option<usize>,
+    /// After a check in which the rule killed something, it rests this long.
+    pub cooldown: Option<Duration>,
+    pub state: RuleState,
+}
+
+/// What a [`Rule`] remembers between checks.
+#[derive(Default)]
+pub struct RuleState {
+    pub kills: usize,
+    last_kill: Option<Instant>,
+    /// When each process first went over the rule's thresholds.
+    over_since: HashMap<Pid, Instant>,
+}
+
+impl Rule {
+    /// Whether the rule reached its `max`.
+    pub fn is_exhausted(&self) -> bool {
+        !self.has_room(0)
+    }
+
+    /// Whether the rule may act on one more process while `in_flight` of its
+    /// signals are still waiting out a grace period.
+    pub fn has_room(&self, in_flight: usize) -> bool {
+        self.max
+            .is_none_or(|max| self.state.kills + in_flight < max)
+    }
+
+    /// Whether the rule may act in this check: below its `max` and out of cooldown.
+    /// A rule deals with everything it matches in one check, then cools down.
+    pub fn is_active(&self) -> bool {
+        !self.is_exhausted()
+            && !self.state.last_kill.is_some_and(|at| {
+                self.cooldown
+                    .is_some_and(|cooldown| at.elapsed() < cooldown)
+            })
+    }
+
+    /// Counts a kill towards `max` and starts the cooldown.
+    pub fn record_kill(&mut self) {
+        self.state.kills += 1;
+        self.state.last_kill = Some(Instant::now());
+    }
+
+    /// Updates how long each process has been over the thresholds. Call once per check.
+    pub fn track(&mut self, sys: &System) {
+        if self.sustain.is_none() {
+            return;
+        }
+        let now = Instant::now();
+        let filters = &self.filters;
+        self.state.over_since.retain(|pid, _| {
+            sys.process(*pid)
+                .is_some_and(|proc| filters.over_threshold(proc))
+        });
+        for (pid, proc) in sys.processes() {
+            if filters.over_threshold(proc) {
+                self.state.over_since.entry(*pid).or_insert(now);
+            }
+        }
+    }
+
+    /// Since when `pid` has been over the thresholds, if the rule tracks that.
+    pub fn over_since(&self, pid: Pid) -> Option<Instant> {
+        self.state.over_since.get(&pid).copied()
+    }
+
+    /// The processes this rule selects right now. With `sustained` unset the
+    /// `for` duration is ignored, for one-off snapshots such as `grim rules check`.
+    pub fn matches(&self, sys: &System, protection: &Protection, sustained: bool) -> Vec<Match> {
+        let held = |proc: &Process| {
+            !sustained
+                || self.sustain.is_none_or(|min| {
+                    self.over_since(proc.pid())
+                        .is_some_and(|since| since.elapsed() >= min)
+                })
+        };
+        collect_matches(sys, &self.targets, protection, |proc| {
+            self.filters.allows(proc) && held(proc)
+        })
+    }
+
+    /// A one-line summary of the action, e.g. `SIGTERM, SIGKILL after 10s, max 5`.
+    pub fn describe_action(&self) -> String {
+        let mut parts = vec![signal_name(self.killer.signal())];
+        if let Some(grace) = self.killer.grace() {
+            parts.push(format!("SIGKILL after {}", format_duration(grace)));
+        }
+        if let Some(wait) = self.killer.wait() {
+            parts.push(format!("checked gone within {}", format_duration(wait)));
+        }
+        if self.kill_children {
+            parts.push("with children".to_string());
+        }
+        if let Some(max) = self.max {
+            parts.push(format!("max {}", max));
+        }
+        if let Some(cooldown) = self.cooldown {
+            parts.push(format!("cooldown {}", format_duration(cooldown)));
+        }
+        if let Some(sustain) = self.sustain {
+            parts.push(format!("once over limits for {}", format_duration(sustain)));
+        }
+        parts.join(", ")
+    }
+}
+
+/// Command-line options that rules inherit unless they set their own.
+pub struct RuleDefaults {
+    pub signal: Option<Signal>,
+    pub grace: Option<Duration>,
+    pub wait: Option<Duration>,
+    pub kill_children: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleFile {
+    #[serde(default)]
+    rule: Vec<RuleSpec>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleSpec {
+    name: String,
+    /// A target pattern: substring by default, `re:`, `glob:` and `exact:` prefixes work.
+    process: Option<String>,
+    /// Shorthand for `process = "re:..."`.
+    regex: Option<String>,
+    #[serde(default)]
+    fields: Vec<String>,
+    #[serde(default)]
+    case_sensitive: bool,
+    user: Option<String>,
+    cpu_above: Option<f32>,
+    mem_above: Option<Size>,
+    older_than: Option<String>,
+    younger_than: Option<String>,
+    #[serde(rename = "for")]
+    sustain: Option<String>,
+    signal: Option<String>,
+    grace: Option<String>,
+    wait: Option<String>,
+    kill_children: Option<bool>,
+    max: Option<usize>,
+    cooldown: Option<String>,
+}
+
+/// Reads a rules file. Problems are reported with the rule's name.
+pub fn load_rules(
+    path: &Path,
+    sys: &System,
+    defaults: &RuleDefaults,
+) -> Result<Vec<Rule>, CliError> {
+    fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| parse_rules(&text, sys, defaults))
+        .map_err(|e| CliError::Rules(format!("{}: {}", path.display(), e)))
+}
+
+pub fn parse_rules(text: &str, sys: &System, defaults: &RuleDefaults) -> Result<Vec<Rule>, String> {
+    let file: RuleFile = toml::from_str(text).map_err(|e| e.message().to_string())?;
+    if file.rule.is_empty() {
+        return Err("no [[rule]] entries".to_string());
+    }
+    let mut rules: Vec<Rule> = vec![];
+    for spec in file.rule {
+        if rules.iter().any(|rule| rule.name == spec.name) {
+            return Err(format!("rule '{}' is defined twice", spec.name));
+        }
+        let name = spec.name.clone();
+        rules.push(compile(spec, sys, defaults).map_err(|e| format!("rule '{}': {}", name, e))?);
+    }
+    Ok(rules)
+}
+
+fn compile(spec: RuleSpec, sys: &System, defaults: &RuleDefaults) -> Result<Rule, String> {
+    let duration = |value: &Option<String>, key: &str| {
+        value
+            .as_deref()
+            .map(|raw| parse_duration(raw).ok_or_else(|| format!("invalid {} '{}'", key, raw)))
+            .transpose()
+    };
+    let fields = spec
+        .fields
+        .iter()
+        .map(|field| MatchField::parse(field).ok_or_else(|| format!("unknown field '{}'", field)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let pattern = match (&spec.process, &spec.regex) {
+        (Some(_), Some(_)) => return Err("set either process or regex, not both".to_string()),
+        (Some(process), None) => Some(process.clone()),
+        (None, Some(regex)) => Some(format!("re:{}", regex)),
+        (None, None) => None,
+    };
+
+    let filters = Filters {
+        user: spec
+            .user
+            .as_deref()
+            .map(|user| resolve_user(sys, user))
+            .transpose()?,
+        cpu_above: spec
+            .cpu_above
+            .map(|pct| {
+                if pct.is_finite() && pct >= 0.0 {
+                    Ok(pct)
+                } else {
+                    Err(format!("invalid cpu_above {}", pct))
+                }
+            })
+            .transpose()?,
+        mem_above: spec
+            .mem_above
+            .map(|size| {
+                size.bytes().and_then(|bytes| match bytes {
+                    0 => Err("mem_above must be above 0".to_string()),
+                    bytes => Ok(bytes),
+                })
+            })
+            .transpose()?,
+        older_than: duration(&spec.older_than, "older_than")?,
+        younger_than: duration(&spec.younger_than, "younger_than")?,
+    };
+    let sustain = duration(&spec.sustain, "for")?;
+    if sustain.is_some() && !filters.has_thresholds() {
+        return Err("for needs cpu_above or mem_above".to_string());
+    }
+
+    let targets = match pattern {
+        Some(pattern) => {
+            let target =
+                Target::parse(&pattern, MatchMode::Substring, &fields, spec.case_sensitive)?;
+            vec![(pattern, target)]
+        }
+        None if filters.is_restrictive() => vec![("all".to_string(), Target::All)],
+        None => return Err("needs process, regex, user, a threshold or an age".to_string()),
+    };
+
+    let grace = duration(&spec.grace, "grace")?.or(defaults.grace);
+    let signal = match &spec.signal {
+        Some(raw) => parse_signal(raw).ok_or_else(|| format!("unknown signal '{}'", raw))?,
+        None => defaults.signal.unwrap_or(if grace.is_some() {
+            Signal::Term
+        } else {
+            Signal::Kill
+        }),
+    };
+
+    Ok(Rule {
+        name: spec.name,
+        targets,
+        filters,
+        sustain,
+        killer: Killer::new(signal)
+            .with_grace(grace)
+            .with_wait(duration(&spec.wait, "wait")?.or(defaults.wait)),
+        kill_children: spec.kill_children.unwrap_or(defaults.kill_children),
+        max: spec.max,
+        cooldown: duration(&spec.cooldown, "cooldown")?,
+        state: RuleState::default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFAULTS: RuleDefaults = RuleDefaults {
+        signal: None,
+        grace: None,
+        wait: None,
+        kill_children: false,
+    };
+
+    fn parse(text: &str) -> Result<Vec<Rule>, String> {
+        parse_rules(text, &System::new(), &DEFAULTS)
+    }
+
+    #[test]
+    fn parses_selection_and_action() {
+        let rules = parse(
+            r#"
+            [[rule]]
+            name = "node hog"
+            regex = "^node$"
+            cpu_above = 90
+            mem_above = "2G"
+            for = "1m"
+            grace = "10s"
+            wait = "3s"
+            max = 3
+            cooldown = "5m"
+
+            [[rule]]
+            name = "old builds"
+            older_than = "1d"
+            signal = "HUP"
+            kill_children = true
+            "#,
+        )
+        .unwrap();
+        assert_eq!(rules.len(), 2);
+
+        let hog = &rules[0];
+        assert_eq!(hog.targets[0].0, "re:^node$");
+        assert_eq!(hog.filters.mem_above, Some(2 * 1024 * 1024 * 1024));
+        assert_eq!(hog.sustain, Some(Duration::from_secs(60)));
+        assert_eq!(hog.killer.signal(), Signal::Term);
+        assert_eq!(hog.killer.grace(), Some(Duration::from_secs(10)));
+        assert_eq!(hog.killer.wait(), Some(Duration::from_secs(3)));
+        assert_eq!(hog.max, Some(3));
+
+        let old = &rules[1];
+        assert!(matches!(old.targets[0].1, Target::All));
+        assert_eq!(old.killer.signal(), Signal::Hangup);
+        assert!(old.kill_children);
+    }
+
+    #[test]
+    fn rules_inherit_command_line_defaults() {
+        let defaults = RuleDefaults {
+            signal: Some(Signal::Interrupt),
+            grace: None,
+            wait: None,
+            kill_children: true,
+        };
+        let rules = parse_rules(
+            "[[rule]]\nname = \"x\"\nprocess = \"x\"",
+            &System::new(),
+            &defaults,
+        )
+        .unwrap();
+        assert_eq!(rules[0].killer.signal(), Signal::Interrupt);
+        assert!(rules[0].kill_children);
+    }
+
+    #[test]
+    fn max_and_cooldown_pause_the_rule() {
+        let mut rule = parse("[[rule]]\nname = \"x\"\nprocess = \"x\"\nmax = 1")
+            .unwrap()
+            .remove(0);
+        assert!(rule.is_active());
+        rule.record_kill();
+        assert!(!rule.is_active());
+
+        let mut rule = parse("[[rule]]\nname = \"x\"\nprocess = \"x\"\ncooldown = \"1h\"")
+            .unwrap()
+            .remove(0);
+        rule.record_kill();
+        assert!(!rule.is_active());
+    }
+
+    #[test]
+    fn rejects_bad_rules() {
+        for bad in [
+            "",
+            "[[rule]]\nname = \"unfiltered\"",
+            "[[rule]]\nname = \"both\"\nprocess = \"a\"\nregex = \"b\"",
+            "[[rule]]\nname = \"x\"\nprocess = \"a\"\nfor = \"1m\"",
+            "[[rule]]\nname = \"x\"\nprocess = \"a\"\nsignal = \"NOPE\"",
+            "[[rule]]\nname = \"x\"\nprocess = \"a\"\ncooldown = \"soon\"",
+            "[[rule]]\nname = \"x\"\nprocess = \"a\"\nmem_above = 0",
+            "[[rule]]\nname = \"x\"\nprocess = \"a\"\ntypo = 1",
+            "[[rule]]\nname = \"x\"\nprocess = \"a\"\n[[rule]]\nname = \"x\"\nprocess = \"b\"",
+        ] {
+            assert!(parse(bad).is_err(), "accepted {:?}", bad);
+        }
+    }
+
+    #[test]
+    fn missing_rules_file_is_a_rules_error() {
+        let missing = Path::new("/nonexistent/grim/rules.toml");
+        let Err(error) = load_rules(missing, &System::new(), &DEFAULTS) else {
+            panic!("loaded {}", missing.display());
+        };
+        assert!(matches!(error, CliError::Rules(_)));
+        assert!(error.to_string().starts_with("bad rules file "));
+    }
+}
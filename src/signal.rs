@@ -0,0 +1,276 @@
+//! Signal names, numbers and delivery.
+
+use std::io;
+#[cfg(target_os = "linux")]
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+#[cfg(unix)]
+use sysinfo::PidExt;
+use sysinfo::{Pid, Process, ProcessExt, Signal};
+
+/// Signals accepted by `--signal`, by their name without the `SIG` prefix.
+pub const SIGNALS: &[(&str, Signal)] = &[
+    ("HUP", Signal::Hangup),
+    ("INT", Signal::Interrupt),
+    ("QUIT", Signal::Quit),
+    ("ILL", Signal::Illegal),
+    ("TRAP", Signal::Trap),
+    ("ABRT", Signal::Abort),
+    ("IOT", Signal::IOT),
+    ("BUS", Signal::Bus),
+    ("FPE", Signal::FloatingPointException),
+    ("KILL", Signal::Kill),
+    ("USR1", Signal::User1),
+    ("SEGV", Signal::Segv),
+    ("USR2", Signal::User2),
+    ("PIPE", Signal::Pipe),
+    ("ALRM", Signal::Alarm),
+    ("TERM", Signal::Term),
+    ("CHLD", Signal::Child),
+    ("CONT", Signal::Continue),
+    ("STOP", Signal::Stop),
+    ("TSTP", Signal::TSTP),
+    ("TTIN", Signal::TTIN),
+    ("TTOU", Signal::TTOU),
+    ("URG", Signal::Urgent),
+    ("XCPU", Signal::XCPU),
+    ("XFSZ", Signal::XFSZ),
+    ("VTALRM", Signal::VirtualAlarm),
+    ("PROF", Signal::Profiling),
+    ("WINCH", Signal::Winch),
+    ("IO", Signal::IO),
+    ("POLL", Signal::Poll),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ("PWR", Signal::Power),
+    ("SYS", Signal::Sys),
+];
+
+/// Parses a signal given as a name (`TERM`, `SIGTERM`, `term`) or a number (`15`).
+pub fn parse_signal(input: &str) -> Option<Signal> {
+    let upper = input.trim().to_uppercase();
+    let name = upper.strip_prefix("SIG").unwrap_or(&upper);
+    if let Ok(num) = name.parse::<i32>() {
+        return SIGNALS
+            .iter()
+            .map(|(_, sig)| *sig)
+            .find(|sig| signal_number(*sig) == Some(num));
+    }
+    SIGNALS
+        .iter()
+        .find(|(candidate, _)| *candidate == name)
+        .map(|(_, sig)| *sig)
+}
+
+/// The conventional `SIGTERM`-style name of `signal`.
+pub fn signal_name(signal: Signal) -> String {
+    SIGNALS
+        .iter()
+        .find(|(_, sig)| *sig == signal)
+        .map(|(name, _)| format!("SIG{}", name))
+        .unwrap_or_else(|| signal.to_string())
+}
+
+/// The platform's number for `signal`, if it has one.
+#[cfg(unix)]
+pub fn signal_number(signal: Signal) -> Option<i32> {
+    Some(match signal {
+        Signal::Hangup => libc::SIGHUP,
+        Signal::Interrupt => libc::SIGINT,
+        Signal::Quit => libc::SIGQUIT,
+        Signal::Illegal => libc::SIGILL,
+        Signal::Trap => libc::SIGTRAP,
+        Signal::Abort => libc::SIGABRT,
+        Signal::Bus => libc::SIGBUS,
+        Signal::FloatingPointException => libc::SIGFPE,
+        Signal::Kill => libc::SIGKILL,
+        Signal::User1 => libc::SIGUSR1,
+        Signal::Segv => libc::SIGSEGV,
+        Signal::User2 => libc::SIGUSR2,
+        Signal::Pipe => libc::SIGPIPE,
+        Signal::Alarm => libc::SIGALRM,
+        Signal::Term => libc::SIGTERM,
+        Signal::Child => libc::SIGCHLD,
+        Signal::Continue => libc::SIGCONT,
+        Signal::Stop => libc::SIGSTOP,
+        Signal::TSTP => libc::SIGTSTP,
+        Signal::TTIN => libc::SIGTTIN,
+        Signal::TTOU => libc::SIGTTOU,
+        Signal::Urgent => libc::SIGURG,
+        Signal::XCPU => libc::SIGXCPU,
+        Signal::XFSZ => libc::SIGXFSZ,
+        Signal::VirtualAlarm => libc::SIGVTALRM,
+        Signal::Profiling => libc::SIGPROF,
+        Signal::Winch => libc::SIGWINCH,
+        Signal::IO => libc::SIGIO,
+        Signal::Sys => libc::SIGSYS,
+        // Aliases; `parse_signal` resolves numbers to the canonical names listed first.
+        Signal::IOT => libc::SIGABRT,
+        Signal::Poll => libc::SIGIO,
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        Signal::Power => libc::SIGPWR,
+        #[allow(unreachable_patterns)]
+        _ => return None,
+    })
+}
+
+#[cfg(not(unix))]
+pub fn signal_number(signal: Signal) -> Option<i32> {
+    (signal == Signal::Kill).then_some(9)
+}
+
+/// Sends `signal` to `proc`, keeping the OS error (e.g. EPERM, ESRCH) on failure.
+#[cfg(unix)]
+pub fn send_signal(proc: &Process, signal: Signal) -> io::Result<()> {
+    let Some(num) = signal_number(signal) else {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{} is not supported on this platform", signal_name(signal)),
+        ));
+    };
+    // SAFETY: kill(2) has no memory-safety preconditions.
+    if unsafe { libc::kill(proc.pid().as_u32() as libc::pid_t, num) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(unix))]
+pub fn send_signal(proc: &Process, signal: Signal) -> io::Result<()> {
+    match proc.kill_with(signal) {
+        Some(true) => Ok(()),
+        Some(false) => Err(io::Error::other("failed to signal process")),
+        None => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{} is not supported on this platform", signal_name(signal)),
+        )),
+    }
+}
+
+/// A handle on one particular process. On Linux kernels with pidfds (5.3+) it
+/// holds one, so a signal sent through it can never reach a later process that
+/// reuses the PID; elsewhere it falls back to signalling by PID.
+#[derive(Debug)]
+pub struct ProcessHandle {
+    pid: Pid,
+    #[cfg(target_os = "linux")]
+    pidfd: Option<OwnedFd>,
+}
+
+impl ProcessHandle {
+    /// Opens a handle on `pid`. Fails with ESRCH only when the kernel says the
+    /// process is already gone; any other pidfd trouble falls back to the PID.
+    #[cfg(target_os = "linux")]
+    pub fn open(pid: Pid) -> io::Result<Self> {
+        // SAFETY: pidfd_open(2) has no memory-safety preconditions.
+        let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid.as_u32() as libc::pid_t, 0) };
+        let pidfd = if fd >= 0 {
+            // SAFETY: the kernel just gave us this descriptor and nothing else owns it.
+            Some(unsafe { OwnedFd::from_raw_fd(fd as libc::c_int) })
+        } else {
+            let e = io::Error::last_os_error();
+            if e.raw_os_error() == Some(libc::ESRCH) {
+                return Err(e);
+            }
+            None
+        };
+        Ok(Self { pid, pidfd })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn open(pid: Pid) -> io::Result<Self> {
+        Ok(Self { pid })
+    }
+
+    pub fn pid(&self) -> Pid {
+        self.pid
+    }
+
+    /// Whether signals go through a pidfd rather than the PID.
+    #[cfg(target_os = "linux")]
+    pub fn is_pidfd(&self) -> bool {
+        self.pidfd.is_some()
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn is_pidfd(&self) -> bool {
+        false
+    }
+
+    /// Sends `signal` to the process the handle was opened on. `proc` is the
+    /// current entry for the PID, used when there is no pidfd.
+    pub fn signal(&self, proc: &Process, signal: Signal) -> io::Result<()> {
+        #[cfg(target_os = "linux")]
+        if let Some(pidfd) = &self.pidfd {
+            let Some(num) = signal_number(signal) else {
+                return send_signal(proc, signal);
+            };
+            // SAFETY: pidfd_send_signal(2) with a valid descriptor and a null siginfo.
+            let sent = unsafe {
+                libc::syscall(
+                    libc::SYS_pidfd_send_signal,
+                    pidfd.as_raw_fd(),
+                    num,
+                    std::ptr::null::<libc::siginfo_t>(),
+                    0,
+                )
+            };
+            return if sent == 0 {
+                Ok(())
+            } else {
+                Err(io::Error::last_os_error())
+            };
+        }
+        send_signal(proc, signal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_signal_names() {
+        assert_eq!(parse_signal("TERM"), Some(Signal::Term));
+        assert_eq!(parse_signal("sigterm"), Some(Signal::Term));
+        assert_eq!(parse_signal(" hup "), Some(Signal::Hangup));
+        assert_eq!(parse_signal("NOPE"), None);
+        assert_eq!(signal_name(Signal::Kill), "SIGKILL");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn parses_signal_numbers() {
+        assert_eq!(parse_signal("9"), Some(Signal::Kill));
+        assert_eq!(parse_signal("15"), Some(Signal::Term));
+        // Aliases resolve to the canonical name listed first.
+        assert_eq!(parse_signal("6"), Some(Signal::Abort));
+        assert_eq!(parse_signal("999"), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn handle_signals_the_process_it_was_opened_on() {
+        use sysinfo::{System, SystemExt};
+
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let pid = Pid::from_u32(child.id());
+        let handle = ProcessHandle::open(pid).unwrap();
+        let mut sys = System::new();
+        assert!(sys.refresh_process(pid));
+        handle
+            .signal(sys.process(pid).unwrap(), Signal::Kill)
+            .unwrap();
+        assert!(!child.wait().unwrap().success());
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn parses_power_signal() {
+        assert_eq!(parse_signal("PWR"), Some(Signal::Power));
+        assert_eq!(parse_signal(&libc::SIGPWR.to_string()), Some(Signal::Power));
+    }
+}
@@ -0,0 +1,631 @@
+//! The interactive Ratatui front-end.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossterm::event::KeyEventKind;
+use crossterm::event::{Event, KeyCode};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+use crossterm::{event, execute};
+use grim::priority::NICE_RANGE;
+use grim::process::process_tree;
+use grim::units::format_duration;
+use grim::{
+    Escalation, KillOutcome, Killer, MatchField, MatchMode, Matcher, ProcessSnapshot, Protection,
+    get_nice, scheduling_policy, set_nice,
+};
+use ratatui::Terminal;
+use ratatui::prelude::{Alignment, Color, Rect};
+use ratatui::prelude::{Constraint, CrosstermBackend, Direction, Layout, Modifier};
+use ratatui::style::Style;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use ratatui::widgets::Block;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::{Borders, List, ListItem};
+use sysinfo::{Pid, PidExt, ProcessExt, ProcessStatus, Signal, System, SystemExt};
+
+use crate::audit::AuditLog;
+use crate::config::Config;
+use crate::frozen::FrozenList;
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
+
+/// Grace period the TUI's `g` toggle uses when none was given on the command line.
+const DEFAULT_TUI_GRACE: Duration = Duration::from_secs(5);
+
+/// How long the TUI checks that a killed process is gone when `--wait` is not given.
+const DEFAULT_TUI_WAIT: Duration = Duration::from_secs(2);
+
+/// Longest the TUI waits for a key before redrawing, so escalations stay on time
+/// even with a slow refresh rate.
+const FRAME_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Green below `warn`, yellow below `critical`, red from there on.
+fn level_color<T: PartialOrd>(value: T, [warn, critical]: [T; 2]) -> Color {
+    if value < warn {
+        Color::Green
+    } else if value < critical {
+        Color::Yellow
+    } else {
+        Color::Red
+    }
+}
+
+/// Signals sent from the TUI: the running kill total, the processes still waiting
+/// out a grace period or being checked, what grim has frozen, and the latest
+/// result for the footer.
+struct TuiKills {
+    escalation: Escalation,
+    /// What each signalled process looked like, and the signal it got, until its
+    /// outcome is known and audited.
+    pending: HashMap<Pid, (ProcessSnapshot, Signal)>,
+    audit: AuditLog,
+    frozen: FrozenList,
+    /// Kills seen through: only processes that were checked to be gone count.
+    total: usize,
+    /// Each process that was not dealt with, and why, for the summary on exit.
+    failures: Vec<String>,
+    last_event: String,
+}
+
+impl TuiKills {
+    fn new(audit: AuditLog, frozen: FrozenList) -> Self {
+        Self {
+            escalation: Escalation::default(),
+            pending: HashMap::new(),
+            audit,
+            frozen,
+            total: 0,
+            failures: vec![],
+            last_event: String::new(),
+        }
+    }
+
+    /// Kills `target` (and its whole descendant tree, leaves first, when
+    /// `kill_children` is set). With a grace period the processes get SIGTERM and
+    /// [`TuiKills::poll`] escalates to SIGKILL on a later frame; either way later
+    /// frames check for up to `wait` that they are gone.
+    fn kill(
+        &mut self,
+        sys: &mut System,
+        protection: &Protection,
+        target: ProcessSnapshot,
+        kill_children: bool,
+        grace: Option<Duration>,
+        wait: Duration,
+    ) {
+        let signal = if grace.is_some() {
+            Signal::Term
+        } else {
+            Signal::Kill
+        };
+        let killer = Killer::new(signal).with_grace(grace).with_wait(Some(wait));
+        self.send(sys, protection, target, kill_children, killer);
+    }
+
+    /// Sends `killer`'s signal to `target`, as selected on screen (see [`TuiKills::kill`]).
+    fn send(
+        &mut self,
+        sys: &mut System,
+        protection: &Protection,
+        target: ProcessSnapshot,
+        kill_children: bool,
+        killer: Killer,
+    ) {
+        let signal = killer.signal();
+        let mut group: Vec<ProcessSnapshot> = vec![];
+        // If the PID went to another process meanwhile, its children are not ours either.
+        let same = sys
+            .process(target.pid)
+            .is_some_and(|proc| target.is_same_process(proc));
+        if kill_children && same {
+            group.extend(
+                process_tree(sys, target.pid)
+                    .iter()
+                    .rev()
+                    .filter_map(|(cpid, _, _)| sys.process(*cpid))
+                    .map(|child| ProcessSnapshot::capture(sys, child)),
+            );
+        }
+        group.push(target);
+        for member in &group {
+            self.pending.insert(member.pid, (member.clone(), signal));
+        }
+
+        let killer = killer.with_protection(protection.clone());
+        let (outcomes, escalation) = killer.start(sys, &group);
+        self.escalation.merge(escalation);
+        self.record(outcomes);
+    }
+
+    /// Finishes off grace periods and waits that ended since the last frame, without blocking.
+    fn poll(&mut self, sys: &mut System) {
+        if !self.escalation.is_empty() {
+            let outcomes = self.escalation.poll(sys);
+            self.record(outcomes);
+        }
+    }
+
+    fn record(&mut self, outcomes: Vec<(Pid, KillOutcome)>) {
+        for (pid, outcome) in outcomes {
+            let Some((process, signal)) = self.pending.remove(&pid) else {
+                continue;
+            };
+            let audit_error = self.audit.record(&process, signal, &outcome, "").err();
+            let name = &process.name;
+            let pid = pid.as_u32();
+            self.last_event = match (&outcome, signal) {
+                (KillOutcome::Signalled, Signal::Stop) => format!("PID {} ({}) frozen", pid, name),
+                (KillOutcome::Signalled, Signal::Continue) => {
+                    format!("PID {} ({}) thawed", pid, name)
+                }
+                (KillOutcome::Signalled, _) => format!("PID {} ({}) signalled", pid, name),
+                (KillOutcome::Exited, _) => format!("PID {} ({}) exited cleanly", pid, name),
+                (KillOutcome::ForceKilled, _) => format!("PID {} ({}) force-killed", pid, name),
+                (KillOutcome::Zombie, _) => {
+                    format!("PID {} ({}) killed, now a zombie until reaped", pid, name)
+                }
+                (KillOutcome::Protected, _) => format!("PID {} is protected", pid),
+                (_, _) => format!(
+                    "PID {} ({}) not killed: {}",
+                    pid,
+                    name,
+                    outcome.failure().unwrap_or_default()
+                ),
+            };
+            if let Some(reason) = outcome.failure() {
+                self.failures
+                    .push(format!("PID {} ({}): {}", pid, name, reason));
+            }
+            if outcome.is_success() {
+                match signal {
+                    Signal::Stop => self.frozen.add(&process),
+                    Signal::Continue => self.frozen.remove(process.pid),
+                    // A bare `Signalled` was never checked, so it does not count.
+                    _ if !matches!(outcome, KillOutcome::Signalled) => self.total += 1,
+                    _ => {}
+                }
+                if matches!(signal, Signal::Stop | Signal::Continue) {
+                    self.save_frozen();
+                }
+            }
+            if let Some(e) = audit_error {
+                self.last_event
+                    .push_str(&format!(" (cannot write audit log: {})", e));
+            }
+        }
+    }
+
+    /// Moves the nice value of `pid` by `delta`: `+1` lowers its priority, `-1` raises it.
+    fn renice(&mut self, sys: &System, protection: &Protection, pid: Pid, delta: i32) {
+        let Some(proc) = sys.process(pid) else {
+            return;
+        };
+        if protection.protects(proc) {
+            self.last_event = format!("PID {} is protected", pid.as_u32());
+            return;
+        }
+        let process = ProcessSnapshot::capture(sys, proc);
+        let nice = match get_nice(pid) {
+            Ok(nice) => nice,
+            Err(e) => {
+                self.last_event =
+                    format!("PID {} ({}) not reniced: {}", pid.as_u32(), process.name, e);
+                return;
+            }
+        };
+        let target = (nice + delta).clamp(*NICE_RANGE.start(), *NICE_RANGE.end());
+        let outcome = match set_nice(pid, target) {
+            Ok(()) => KillOutcome::Signalled,
+            Err(e) => KillOutcome::Failed(e),
+        };
+        let audit_error = self
+            .audit
+            .record_action(&process, &format!("nice {}", target), &outcome, "")
+            .err();
+        self.last_event = match outcome {
+            KillOutcome::Failed(e) => {
+                format!("PID {} ({}) not reniced: {}", pid.as_u32(), process.name, e)
+            }
+            _ => format!(
+                "PID {} ({}) nice {} → {}",
+                pid.as_u32(),
+                process.name,
+                nice,
+                target
+            ),
+        };
+        if let Some(e) = audit_error {
+            self.last_event
+                .push_str(&format!(" (cannot write audit log: {})", e));
+        }
+    }
+
+    fn save_frozen(&mut self) {
+        if let Err(e) = self.frozen.save() {
+            self.last_event = format!("cannot save frozen processes: {}", e);
+        }
+    }
+}
+
+pub fn grim_interactive(
+    grace: Option<Duration>,
+    wait: Option<Duration>,
+    excludes: Vec<Matcher>,
+    config: &Config,
+    audit: AuditLog,
+) -> Result<(), Box<dyn Error>> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, crossterm::terminal::EnterAlternateScreen)?;
+
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    // The process as it was when `k` was pressed, and whether Yes is selected.
+    let mut confirm_dialog: Option<(ProcessSnapshot, bool)> = None;
+    let mut sys = System::new_all();
+    let protection = Protection::new(&sys, excludes);
+    let mut selected_idx = 0;
+    let mut kill_children = config.defaults.kill_children;
+    let mut force = config.defaults.force;
+    let mut grace_enabled = grace.is_some();
+    let grace_period = grace.unwrap_or(DEFAULT_TUI_GRACE);
+    let wait = wait.unwrap_or(DEFAULT_TUI_WAIT);
+    let mut kills = TuiKills::new(audit, FrozenList::load());
+
+    let mut filter = String::new();
+    let mut filter_mode = false;
+    let refresh_rate = config.tui.refresh_rate;
+    let colors = &config.tui.colors;
+    let mut last_refresh: Option<Instant> = None;
+
+    loop {
+        if last_refresh.is_none_or(|at| at.elapsed() >= refresh_rate) {
+            sys.refresh_all();
+            last_refresh = Some(Instant::now());
+            if kills.frozen.prune(&sys) {
+                kills.save_frozen();
+            }
+        }
+
+        kills.poll(&mut sys);
+        let all_processes: Vec<(Pid, String, f32, u64, u64)> = sys
+            .processes()
+            .iter()
+            .map(|(pid, proc)| {
+                (
+                    *pid,
+                    proc.name().to_string(),
+                    proc.cpu_usage(),
+                    proc.memory(),
+                    proc.run_time(),
+                )
+            })
+            .collect();
+
+        let filter_matcher = if filter.is_empty() {
+            None
+        } else {
+            Some(Matcher::parse(
+                &filter,
+                MatchMode::Substring,
+                &[MatchField::Name],
+                false,
+            ))
+        };
+        let processes: Vec<_> = match &filter_matcher {
+            Some(Ok(matcher)) => all_processes
+                .into_iter()
+                .filter(|(pid, _, _, _, _)| sys.process(*pid).is_some_and(|p| matcher.is_match(p)))
+                .collect(),
+            _ => all_processes,
+        };
+
+        terminal.draw(|f| {
+            let layout = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(if filter_mode {
+                    vec![Constraint::Length(3), Constraint::Min(1), Constraint::Length(2)]
+                } else {
+                    vec![Constraint::Min(1), Constraint::Length(2)]
+                })
+                .split(f.area());
+
+            let main_chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(70), Constraint::Percentage(30)])
+                .split(layout[if filter_mode {1} else {0}]);
+
+            let mut state = ratatui::widgets::ListState::default();
+            if !processes.is_empty() {
+                selected_idx = selected_idx.min(processes.len() - 1);
+                state.select(Some(selected_idx));
+            }
+
+            let items: Vec<ListItem> = processes.iter()
+                .map(|(pid, name, cpu, mem, uptime)| {
+                    let cpu_color = level_color(*cpu, colors.cpu);
+                    let mem_color = level_color(*mem, colors.memory);
+
+                    let marker = if kills.frozen.contains(*pid) {
+                        Span::styled("❄ ", Style::default().fg(Color::Cyan))
+                    } else if sys.process(*pid).is_some_and(|p| p.status() == ProcessStatus::Stop) {
+                        Span::styled("⏸ ", Style::default().fg(Color::Yellow))
+                    } else {
+                        Span::raw("  ")
+                    };
+
+                    ListItem::new(Line::from(vec![
+                        marker,
+                        Span::raw(format!("PID: {:<5} {:<15} CPU:", pid.as_u32(), name)),
+                        Span::styled(format!("{:>4.1}%", cpu), Style::default().fg(cpu_color)),
+                        Span::raw(" MEM:"),
+                        Span::styled(format!("{:>7.1} MB", *mem as f64 / 1024.0 / 1024.0), Style::default().fg(mem_color)),
+                        Span::raw(format!(" UP: {}s", uptime)),
+                    ]))
+                }).collect();
+
+            let list = List::new(items)
+                .block(Block::default().borders(Borders::ALL)
+                    .title(format!("Processes [{} shown]", processes.len())))
+                .highlight_symbol("➤ ");
+            f.render_stateful_widget(list, main_chunks[0], &mut state);
+
+            let details = if let Some((pid, name, _, _, _)) = processes.get(selected_idx) {
+                let proc = sys.process(*pid).unwrap();
+                let parent_pid = proc.parent().map(|p| p.as_u32()).unwrap_or(0);
+                let mut children = String::new();
+                for (child_pid, child_name, depth) in process_tree(&sys, *pid) {
+                    children.push_str(&format!("{}↳ {} ({})\n", " ".repeat(depth * 2 - 1), child_pid.as_u32(), child_name));
+                }
+                if children.is_empty() { children.push_str(" (none)\n"); }
+
+                let nice = get_nice(*pid).map(|nice| nice.to_string()).unwrap_or_else(|_| "?".to_string());
+                let policy = scheduling_policy(*pid).unwrap_or("?");
+
+                format!(
+                    "PID: {}\nName: {}\nCMD: {}\nParent PID: {}\nCPU: {:.2}%\nMEM: {:.2} MB\nNice: {}\nPolicy: {}\nUptime: {}s\nChildren:\n{}",
+                    pid.as_u32(), name, proc.cmd().join(" "), parent_pid,
+                    proc.cpu_usage(), proc.memory() as f64 / 1024.0 / 1024.0, nice, policy, proc.run_time(), children
+                )
+            } else { "No process selected.".to_string() };
+
+            let side_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(if kills.frozen.processes.is_empty() {
+                    vec![Constraint::Min(1)]
+                } else {
+                    vec![Constraint::Min(1), Constraint::Length(kills.frozen.processes.len().min(8) as u16 + 2)]
+                })
+                .split(main_chunks[1]);
+            f.render_widget(Paragraph::new(details)
+                                .block(Block::default().borders(Borders::ALL).title("Details")), side_chunks[0]);
+
+            if let Some(area) = side_chunks.get(1) {
+                let frozen: Vec<Line> = kills.frozen.processes.iter()
+                    .map(|p| Line::from(format!("❄ {} ({}) since {}", p.pid, p.name, p.since)))
+                    .collect();
+                f.render_widget(Paragraph::new(frozen)
+                                    .block(Block::default().borders(Borders::ALL).title("Frozen by grim")), *area);
+            }
+
+            let footer_text = format!(
+                "[↑↓] Move  [k] Kill  [z] Freeze  [u] Thaw  [+/-] Nice  [r] Refresh  [c] children={} [f] force={} [g] grace={} [w]  [/] filter  [q] Quit | Total killed: {} {}",
+                kill_children,
+                force,
+                if grace_enabled { format_duration(grace_period) } else { "off".to_string() },
+                kills.total,
+                kills.last_event
+            );
+            f.render_widget(Paragraph::new(footer_text)
+                                .block(Block::default().borders(Borders::TOP)), *layout.last().unwrap());
+
+            if filter_mode {
+                let title = match &filter_matcher {
+                    Some(Err(e)) => e.clone(),
+                    _ => "Filter — re:, glob:, exact: prefixes supported (ESC to cancel)".to_string(),
+                };
+                f.render_widget(Paragraph::new(filter.clone())
+                                    .block(Block::default().borders(Borders::ALL).title(title)),
+                                layout[0]);
+            }
+
+            if let Some((target, yes_selected)) = &confirm_dialog {
+                let pid = target.pid;
+                let tree = if kill_children { protection.prune(&sys, process_tree(&sys, pid)) } else { vec![] };
+                let area = centered_rect(60, if tree.is_empty() { 20 } else { 50 }, f.area());
+                let title = if !sys.process(pid).is_some_and(|proc| target.is_same_process(proc)) {
+                    format!("PID {} ({}) is gone", pid.as_u32(), target.name)
+                } else if tree.is_empty() {
+                    format!("Kill PID {} ({})?", pid.as_u32(), target.name)
+                } else {
+                    format!("Kill PID {} ({}) and {} descendant(s)?", pid.as_u32(), target.name, tree.len())
+                };
+                let block = Block::default()
+                    .title(title)
+                    .borders(Borders::ALL);
+                let line = Line::from(vec![
+                    Span::styled("  [ Yes ]  ",
+                                 if *yes_selected { Style::default().fg(Color::Black).bg(Color::Green).add_modifier(Modifier::BOLD) } else { Style::default() }),
+                    Span::raw("    "),
+                    Span::styled("  [ No ]  ",
+                                 if !*yes_selected { Style::default().fg(Color::Black).bg(Color::Red).add_modifier(Modifier::BOLD) } else { Style::default() }),
+                ]);
+                let mut lines: Vec<Line> = tree.iter()
+                    .map(|(cpid, cname, depth)| Line::from(format!("{}↳ {} ({})", "  ".repeat(depth - 1), cpid.as_u32(), cname)))
+                    .collect();
+                if !lines.is_empty() {
+                    lines.push(Line::from(""));
+                }
+                lines.push(line);
+                f.render_widget(Paragraph::new(lines).block(block).alignment(Alignment::Center), area);
+            }
+        })?;
+
+        let until_refresh = last_refresh
+            .map(|at| refresh_rate.saturating_sub(at.elapsed()))
+            .unwrap_or_default();
+        if event::poll(until_refresh.min(FRAME_INTERVAL))?
+            && let Event::Key(key) = event::read()?
+        {
+            if let Some((target, mut yes_selected)) = confirm_dialog.take() {
+                if key.kind == KeyEventKind::Press {
+                    match key.code {
+                        KeyCode::Left | KeyCode::Right => {
+                            yes_selected = !yes_selected;
+                            confirm_dialog = Some((target, yes_selected));
+                        }
+                        KeyCode::Enter => {
+                            if yes_selected {
+                                kills.kill(
+                                    &mut sys,
+                                    &protection,
+                                    target,
+                                    kill_children,
+                                    grace_enabled.then_some(grace_period),
+                                    wait,
+                                );
+                                last_refresh = None;
+                            }
+                        }
+                        KeyCode::Esc => {}
+                        _ => {
+                            confirm_dialog = Some((target, yes_selected));
+                        }
+                    }
+                } else {
+                    confirm_dialog = Some((target, yes_selected));
+                }
+                continue;
+            }
+
+            if key.kind == KeyEventKind::Press {
+                match key.code {
+                    KeyCode::Char('/') => {
+                        filter_mode = true;
+                        filter.clear();
+                    }
+                    KeyCode::Enter if filter_mode => filter_mode = false,
+                    KeyCode::Esc if filter_mode => {
+                        filter_mode = false;
+                        filter.clear();
+                    }
+                    KeyCode::Char(c) if filter_mode => filter.push(c),
+                    KeyCode::Backspace if filter_mode => {
+                        filter.pop();
+                    }
+
+                    KeyCode::Char('q') => break,
+                    KeyCode::Down if !filter_mode && selected_idx + 1 < processes.len() => {
+                        selected_idx += 1;
+                    }
+                    KeyCode::Up if !filter_mode => {
+                        selected_idx = selected_idx.saturating_sub(1);
+                    }
+                    KeyCode::Char('r') if !filter_mode => last_refresh = None,
+                    KeyCode::Char('c') if !filter_mode => kill_children = !kill_children,
+                    KeyCode::Char('f') if !filter_mode => force = !force,
+                    KeyCode::Char('g') if !filter_mode => grace_enabled = !grace_enabled,
+
+                    KeyCode::Char('k') if !filter_mode => {
+                        if let Some(proc) = processes
+                            .get(selected_idx)
+                            .and_then(|(pid, ..)| sys.process(*pid))
+                        {
+                            let target = ProcessSnapshot::capture(&sys, proc);
+                            if protection.protects(proc) {
+                                kills.last_event =
+                                    format!("PID {} is protected", target.pid.as_u32());
+                            } else if force {
+                                kills.kill(
+                                    &mut sys,
+                                    &protection,
+                                    target,
+                                    kill_children,
+                                    grace_enabled.then_some(grace_period),
+                                    wait,
+                                );
+                                last_refresh = None;
+                            } else {
+                                confirm_dialog = Some((target, true));
+                            }
+                        }
+                    }
+                    KeyCode::Char(key @ ('+' | '-')) if !filter_mode => {
+                        if let Some((pid, _, _, _, _)) = processes.get(selected_idx) {
+                            kills.renice(&sys, &protection, *pid, if key == '+' { 1 } else { -1 });
+                        }
+                    }
+                    KeyCode::Char(key @ ('z' | 'u')) if !filter_mode => {
+                        if let Some(proc) = processes
+                            .get(selected_idx)
+                            .and_then(|(pid, ..)| sys.process(*pid))
+                        {
+                            let target = ProcessSnapshot::capture(&sys, proc);
+                            let signal = if key == 'z' {
+                                Signal::Stop
+                            } else {
+                                Signal::Continue
+                            };
+                            kills.send(
+                                &mut sys,
+                                &protection,
+                                target,
+                                kill_children,
+                                Killer::new(signal),
+                            );
+                            last_refresh = None;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        crossterm::terminal::LeaveAlternateScreen
+    )?;
+    if !kills.escalation.is_empty() {
+        println!(
+            "⏳ Seeing {} signalled process(es) through...",
+            kills.escalation.len()
+        );
+        while !kills.escalation.is_empty() {
+            thread::sleep(FRAME_INTERVAL);
+            kills.poll(&mut sys);
+        }
+    }
+    if !kills.failures.is_empty() {
+        println!("❌ {} process(es) not killed:", kills.failures.len());
+        for failure in &kills.failures {
+            println!("    {}", failure);
+        }
+    }
+    println!("🎯 Done. Total processes killed: {}", kills.total);
+    Ok(())
+}